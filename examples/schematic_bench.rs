@@ -0,0 +1,171 @@
+//! Compares `engine::Schematic` with the hash map based version it replaced, on a random
+//! schematic of SIZE×SIZE cells.
+//!
+//!     cargo run --release --example schematic_bench -- [SIZE]
+//!
+//! SIZE defaults to 10000, which needs a few GB of memory.
+
+use aoc_2023::{
+    bench::Lcg,
+    engine::{Schematic, SchematicSymbol},
+};
+use simple_grid::{Grid, GridIndex};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    time::{Duration, Instant},
+};
+
+/// The previous implementation, a hash map from every digit to its number.
+mod legacy {
+    use super::*;
+
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+    struct Part {
+        start: GridIndex,
+        value: u32,
+    }
+
+    pub struct Schematic<'a> {
+        grid: &'a Grid<SchematicSymbol>,
+        parts: Vec<Part>,
+        index_to_part: HashMap<GridIndex, usize>,
+    }
+
+    impl<'a> Schematic<'a> {
+        pub fn new(grid: &'a Grid<SchematicSymbol>) -> Self {
+            let mut parts = Vec::new();
+            let mut index_to_part = HashMap::new();
+            for end in grid.indices() {
+                let is_number = |idx: Option<GridIndex>| {
+                    idx.is_some_and(|idx| matches!(grid.get(idx), Some(SchematicSymbol::Number(_))))
+                };
+                if !is_number(Some(end)) || is_number(end.right()) {
+                    continue;
+                }
+                let mut value = 0;
+                for l in 0.. {
+                    let idx = GridIndex::new(end.column() - l, end.row());
+                    index_to_part.insert(idx, parts.len());
+                    if let SchematicSymbol::Number(digit) = grid[idx] {
+                        value += digit * 10_u32.pow(l as u32);
+                    }
+                    if !is_number(idx.left()) {
+                        parts.push(Part { start: idx, value });
+                        break;
+                    }
+                }
+            }
+
+            Self {
+                grid,
+                parts,
+                index_to_part,
+            }
+        }
+
+        pub fn part_numbers(&self) -> Vec<u32> {
+            let mut set = HashSet::new();
+            for (idx, part) in &self.index_to_part {
+                for neighbor in idx.neighbors() {
+                    if let Some(SchematicSymbol::Symbol(_)) = self.grid.get(neighbor) {
+                        set.insert(self.parts[*part]);
+                    }
+                }
+            }
+            set.iter().map(|v| v.value).collect()
+        }
+
+        pub fn gear_ratios(&self) -> Vec<u32> {
+            let mut gears = Vec::new();
+            for idx in self.grid.indices() {
+                if let Some(SchematicSymbol::Symbol('*')) = self.grid.get(idx) {
+                    let mut adjacent_parts = HashSet::new();
+                    for neighbor in idx.neighbors() {
+                        if let Some(part) = self.index_to_part.get(&neighbor) {
+                            adjacent_parts.insert(self.parts[*part]);
+                        }
+                    }
+                    if adjacent_parts.len() == 2 {
+                        gears.push(adjacent_parts.iter().map(|p| p.value).product());
+                    }
+                }
+            }
+            gears
+        }
+    }
+}
+
+/// Dots, numbers of one to three digits and a few symbols, about as dense as a real input.
+fn random_grid(size: usize) -> Grid<SchematicSymbol> {
+    let mut lcg = Lcg::new(0x2023);
+    let mut random = |n: u64| lcg.below(n);
+    let mut cells = Vec::with_capacity(size * size);
+    for _ in 0..size {
+        let row_start = cells.len();
+        while cells.len() - row_start < size {
+            match random(20) {
+                0..=3 => {
+                    for _ in 0..=random(3) {
+                        cells.push(SchematicSymbol::Number(random(10) as u32));
+                    }
+                    cells.push(SchematicSymbol::Period);
+                }
+                4 => cells.push(SchematicSymbol::Symbol('*')),
+                5 => cells.push(SchematicSymbol::Symbol(
+                    ['#', '$', '+', '/'][random(4) as usize],
+                )),
+                _ => cells.push(SchematicSymbol::Period),
+            }
+        }
+        cells.truncate(row_start + size);
+    }
+
+    Grid::new(size, size, cells)
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
+}
+
+fn main() {
+    let size = match env::args().nth(1) {
+        Some(size) => size.parse().expect("SIZE is a number"),
+        None => 10_000,
+    };
+    let grid = random_grid(size);
+    println!("{size}x{size} schematic");
+
+    let (old, old_build) = timed(|| legacy::Schematic::new(&grid));
+    let (old_parts, old_parts_time) = timed(|| old.part_numbers());
+    let (old_gears, old_gears_time) = timed(|| old.gear_ratios());
+    let old_sums = (
+        old_parts.iter().map(|&v| u64::from(v)).sum::<u64>(),
+        old_gears.iter().map(|&v| u128::from(v)).sum::<u128>(),
+    );
+    drop(old);
+
+    let (new, new_build) = timed(|| Schematic::new(grid).expect("numbers fit in a u64"));
+    let (new_parts, new_parts_time) = timed(|| new.part_numbers());
+    let (new_gears, new_gears_time) = timed(|| new.gear_ratios());
+    let new_sums = (
+        new_parts.iter().sum::<u64>(),
+        new_gears.iter().sum::<u128>(),
+    );
+    assert_eq!(old_sums, new_sums, "both versions agree");
+
+    println!("{:<14}{:>12}{:>12}", "", "hash map", "dense");
+    for (name, old, new) in [
+        ("build", old_build, new_build),
+        ("part numbers", old_parts_time, new_parts_time),
+        ("gear ratios", old_gears_time, new_gears_time),
+    ] {
+        println!(
+            "{name:<14}{:>10.0}ms{:>10.0}ms",
+            old.as_secs_f64() * 1000.0,
+            new.as_secs_f64() * 1000.0
+        );
+    }
+}
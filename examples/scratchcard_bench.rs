@@ -0,0 +1,116 @@
+//! Compares `scratch::Card` with the hash set based version it replaced, on COUNT random
+//! cards shaped like the puzzle's: 10 winning numbers and 25 numbers in hand, all below 100.
+//!
+//!     cargo run --release --example scratchcard_bench -- [COUNT]
+//!
+//! COUNT defaults to ten million. Cards are parsed and scored one line at a time, only the
+//! match counts are kept to count the copies.
+
+use aoc_2023::{bench::Lcg, error::ParseError, parse::InputLine, scratch::Card};
+use std::{
+    collections::HashSet,
+    env,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+/// The previous card, two hash sets.
+mod legacy {
+    use super::*;
+
+    pub struct Card {
+        winning: HashSet<u32>,
+        hand: HashSet<u32>,
+    }
+
+    impl Card {
+        pub fn parse(s: &str) -> Result<Self, ParseError> {
+            let line = InputLine::new(1, s);
+            let card = line.strip_prefix(s.trim(), "Card ")?;
+            let (id, numbers) = line.split_once(card.trim(), ": ")?;
+            let _: u32 = line.parse(id, "a card id")?;
+            let (winning, hand) = line.split_once(numbers, " | ")?;
+            let winning = winning
+                .split_whitespace()
+                .map(|p| line.parse(p, "a winning number"))
+                .collect::<Result<_, _>>()?;
+            let hand = hand
+                .split_whitespace()
+                .map(|p| line.parse(p, "a number"))
+                .collect::<Result<_, _>>()?;
+
+            Ok(Self { winning, hand })
+        }
+
+        pub fn match_count(&self) -> usize {
+            self.winning.intersection(&self.hand).count()
+        }
+    }
+}
+
+fn random_cards(count: usize) -> String {
+    let mut lcg = Lcg::new(0x2023);
+    let mut random = |n: u64| lcg.below(n);
+    let mut cards = String::with_capacity(count * 120);
+    for id in 1..=count {
+        write!(cards, "Card {id}:").unwrap();
+        for _ in 0..10 {
+            write!(cards, " {:>2}", random(100)).unwrap();
+        }
+        cards.push_str(" |");
+        for _ in 0..25 {
+            write!(cards, " {:>2}", random(100)).unwrap();
+        }
+        cards.push('\n');
+    }
+    cards
+}
+
+/// Points of every card and the total number of cards, prizes past the end are dropped.
+fn score(match_counts: &[usize]) -> (u64, u64) {
+    let points = match_counts
+        .iter()
+        .map(|&n| if n == 0 { 0 } else { 1 << (n - 1) })
+        .sum();
+    let mut copies = vec![1_u64; match_counts.len()];
+    for (i, &n) in match_counts.iter().enumerate() {
+        for j in i + 1..(i + 1 + n).min(copies.len()) {
+            copies[j] += copies[i];
+        }
+    }
+    (points, copies.iter().sum())
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
+}
+
+fn main() {
+    let count = match env::args().nth(1) {
+        Some(count) => count.parse().expect("COUNT is a number"),
+        None => 10_000_000,
+    };
+    let cards = random_cards(count);
+    println!("{count} cards, {} MB", cards.len() / 1_000_000);
+
+    let (old, old_time) = timed(|| {
+        let counts: Vec<_> = cards
+            .lines()
+            .map(|l| legacy::Card::parse(l).unwrap().match_count())
+            .collect();
+        score(&counts)
+    });
+    let (new, new_time) = timed(|| {
+        let counts: Vec<_> = cards
+            .lines()
+            .map(|l| l.parse::<Card>().unwrap().match_count())
+            .collect();
+        score(&counts)
+    });
+    assert_eq!(old, new, "both versions agree");
+
+    println!("hash sets {:>8.0}ms", old_time.as_secs_f64() * 1000.0);
+    println!("bitsets   {:>8.0}ms", new_time.as_secs_f64() * 1000.0);
+}
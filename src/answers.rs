@@ -0,0 +1,564 @@
+use crate::{
+    error::ParseError,
+    input::InputStore,
+    parse::{input_lines, InputLine},
+    sha256,
+    solution::{self, ParsedInput},
+};
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fmt::Display,
+    hash::BuildHasher,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Answers file, relative to the working directory.
+pub const DEFAULT_ANSWERS_FILE: &str = "answers.toml";
+
+/// The known answer for one part of one day, for one input file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    day: u32,
+    part: u32,
+    input: String,
+    expected: Expected,
+}
+
+impl Answer {
+    pub fn new(day: u32, part: u32, input: impl Into<String>, expected: Expected) -> Self {
+        Self {
+            day,
+            part,
+            input: input.into(),
+            expected,
+        }
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn part(&self) -> u32 {
+        self.part
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn expected(&self) -> &Expected {
+        &self.expected
+    }
+}
+
+const HEADER: &str = "\
+# Known answers, keyed by day, part and input file name, checked by `aoc verify`.
+# Answers for private inputs are stored as salted SHA-256 digests, add new ones with `aoc record`.
+";
+
+const SHA256_PREFIX: &str = "sha256:";
+
+/// An expected answer, either in plain text or as a salted SHA-256 digest.
+///
+/// Digests are written as `sha256:{salt}:{hex digest of salt + answer}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    Plain(String),
+    Hashed { salt: String, digest: [u8; 32] },
+}
+
+impl Expected {
+    pub fn plain(answer: impl Into<String>) -> Self {
+        Expected::Plain(answer.into())
+    }
+
+    /// Hashes `answer` with a fresh random salt.
+    pub fn hashed(answer: &str) -> Self {
+        Self::hashed_with_salt(answer, &new_salt())
+    }
+
+    pub fn hashed_with_salt(answer: &str, salt: &str) -> Self {
+        Expected::Hashed {
+            salt: salt.to_string(),
+            digest: salted_digest(salt, answer),
+        }
+    }
+
+    pub fn is_hashed(&self) -> bool {
+        matches!(self, Expected::Hashed { .. })
+    }
+
+    pub fn matches(&self, actual: &str) -> bool {
+        match self {
+            Expected::Plain(answer) => answer == actual,
+            Expected::Hashed { salt, digest } => salted_digest(salt, actual) == *digest,
+        }
+    }
+}
+
+fn salted_digest(salt: &str, answer: &str) -> [u8; 32] {
+    let mut hasher = sha256::Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(answer.as_bytes());
+    hasher.finish()
+}
+
+/// Salts only need to differ between answers, std's randomly keyed hasher is good enough.
+fn new_salt() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{:016x}", RandomState::new().hash_one(now))
+}
+
+impl FromStr for Expected {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(hashed) = s.strip_prefix(SHA256_PREFIX) else {
+            return Ok(Expected::Plain(s.to_string()));
+        };
+        let (salt, hex) = hashed
+            .split_once(':')
+            .ok_or_else(|| ParseError::new(s, "'sha256:{salt}:{digest}'"))?;
+        let digest = sha256::from_hex(hex)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| ParseError::new(hex, "a hex encoded SHA-256 digest"))?;
+
+        Ok(Expected::Hashed {
+            salt: salt.to_string(),
+            digest,
+        })
+    }
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expected::Plain(answer) => write!(f, "{answer}"),
+            Expected::Hashed { salt, digest } => {
+                write!(f, "{SHA256_PREFIX}{salt}:{}", sha256::to_hex(digest))
+            }
+        }
+    }
+}
+
+/// Known answers, stored as a small subset of TOML:
+///
+/// ```toml
+/// [day1.part1]
+/// "example1.txt" = 142
+/// "input.txt" = "sha256:3f0c9a1e5b7d2468:..."
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    answers: Vec<Answer>,
+}
+
+impl Answers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AnswersError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|error| AnswersError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        contents.parse().map_err(|error| AnswersError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+
+    /// Writes the answers with a short header, comments in an existing file are lost.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), AnswersError> {
+        let path = path.as_ref();
+        std::fs::write(path, format!("{HEADER}\n{self}")).map_err(|error| AnswersError::Io {
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+
+    /// Adds an answer, replacing any earlier answer for the same day, part and input.
+    pub fn insert(&mut self, answer: Answer) {
+        match self.answers.iter_mut().find(|a| {
+            (a.day, a.part, a.input.as_str()) == (answer.day, answer.part, answer.input.as_str())
+        }) {
+            Some(existing) => *existing = answer,
+            None => self.answers.push(answer),
+        }
+    }
+
+    pub fn get(&self, day: u32, part: u32, input: &str) -> Option<&Answer> {
+        self.answers
+            .iter()
+            .find(|a| a.day == day && a.part == part && a.input == input)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Answer> {
+        self.answers.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.answers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+
+    fn parse_section(line: InputLine<'_>, text: &str) -> Result<(u32, u32), ParseError> {
+        let section = line.strip_prefix(text, "[")?;
+        let section = section
+            .strip_suffix(']')
+            .ok_or_else(|| line.error(&section[section.len()..], "']'"))?;
+        let (day, part) = line.split_once(section, ".")?;
+        let day = line.strip_prefix(day, "day")?;
+        let part = line.strip_prefix(part, "part")?;
+
+        Ok((
+            line.parse(day, "a day number")?,
+            line.parse(part, "a part number")?,
+        ))
+    }
+
+    fn parse_entry(line: InputLine<'_>, text: &str) -> Result<(String, Expected), ParseError> {
+        let (key, value) = line.split_once(text, "=")?;
+        let key = unquote(line, key.trim())?;
+        let value = value.trim();
+        let value = if value.starts_with('"') {
+            unquote(line, value)?
+        } else {
+            line.parse::<i64>(value, "a number or a quoted string")?;
+            value
+        };
+        let expected = value.parse().map_err(|e| line.locate(value, e))?;
+
+        Ok((key.to_string(), expected))
+    }
+}
+
+fn unquote<'a>(line: InputLine<'a>, text: &'a str) -> Result<&'a str, ParseError> {
+    let inner = line.strip_prefix(text, "\"")?;
+    let inner = inner
+        .strip_suffix('"')
+        .ok_or_else(|| line.error(&inner[inner.len()..], "'\"'"))?;
+    if let Some(i) = inner.find(['"', '\\']) {
+        return Err(line.error(&inner[i..], "a string without quotes or escapes"));
+    }
+
+    Ok(inner)
+}
+
+impl FromStr for Answers {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut answers = Self::new();
+        let mut section = None;
+        for line in input_lines(s) {
+            let text = line.text().trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            if text.starts_with('[') {
+                section = Some(Self::parse_section(line, text)?);
+                continue;
+            }
+            let (day, part) =
+                section.ok_or_else(|| line.error(text, "a '[dayN.partP]' section"))?;
+            let (input, value) = Self::parse_entry(line, text)?;
+            answers.insert(Answer::new(day, part, input, value));
+        }
+
+        Ok(answers)
+    }
+}
+
+/// Writes answers grouped by day and part, in ascending order.
+impl Display for Answers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut answers: Vec<_> = self.answers.iter().collect();
+        answers.sort_by_key(|a| (a.day, a.part));
+
+        let mut section = None;
+        for answer in answers {
+            if section != Some((answer.day, answer.part)) {
+                if section.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[day{}.part{}]", answer.day, answer.part)?;
+                section = Some((answer.day, answer.part));
+            }
+            match &answer.expected {
+                Expected::Plain(value) if value.parse::<i64>().is_ok() => {
+                    writeln!(f, "\"{}\" = {value}", answer.input)?
+                }
+                expected => writeln!(f, "\"{}\" = \"{expected}\"", answer.input)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Io { path: PathBuf, error: io::Error },
+    Parse { path: PathBuf, error: ParseError },
+}
+
+impl Display for AnswersError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnswersError::Io { path, error } => {
+                write!(f, "could not access '{}': {error}", path.display())
+            }
+            AnswersError::Parse { path, error } => write!(f, "{}: {error}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for AnswersError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail {
+        actual: String,
+    },
+    /// The answer could not be checked, e.g. because the input is missing.
+    Unknown {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    answer: Answer,
+    status: Status,
+}
+
+impl Verification {
+    pub fn answer(&self) -> &Answer {
+        &self.answer
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+}
+
+/// Runs the registered solver for every answer and compares the results.
+///
+/// Each input is only parsed once, even if it has answers for both parts.
+pub fn verify(answers: &Answers, store: &InputStore) -> Vec<Verification> {
+    let mut parsed: HashMap<(u32, &str), Result<_, _>> = HashMap::new();
+
+    answers
+        .iter()
+        .map(|answer| {
+            let input = parsed
+                .entry((answer.day, answer.input.as_str()))
+                .or_insert_with(|| parse_input(store, answer.day, &answer.input));
+            let status = match input {
+                Err(reason) => Status::Unknown {
+                    reason: reason.clone(),
+                },
+                Ok(input) => match input.solve(answer.part) {
+                    None => Status::Unknown {
+                        reason: format!("no part {}", answer.part),
+                    },
+                    Some(actual) if answer.expected.matches(&actual) => Status::Pass,
+                    Some(actual) => Status::Fail { actual },
+                },
+            };
+
+            Verification {
+                answer: answer.clone(),
+                status,
+            }
+        })
+        .collect()
+}
+
+/// Solves both parts of every registered day (or just `day`) for the input `name`,
+/// adding the answers that are not known yet. Days without that input are skipped.
+///
+/// Returns the newly recorded answers.
+pub fn record(
+    answers: &mut Answers,
+    store: &InputStore,
+    day: Option<u32>,
+    name: &str,
+    hash: bool,
+) -> Result<Vec<Answer>, String> {
+    let mut recorded = Vec::new();
+    for solution in solution::all().filter(|s| day.is_none_or(|d| s.day() == d)) {
+        let day = solution.day();
+        let missing: Vec<_> = [1, 2]
+            .into_iter()
+            .filter(|&part| answers.get(day, part, name).is_none())
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+        let input = match parse_input(store, day, name) {
+            Ok(input) => input,
+            Err(reason) if reason == NO_INPUT => continue,
+            Err(reason) => return Err(format!("day {day}: {reason}")),
+        };
+        for part in missing {
+            let actual = input.solve(part).expect("parts 1 and 2 always exist");
+            let expected = match hash {
+                true => Expected::hashed(&actual),
+                false => Expected::plain(actual),
+            };
+            let answer = Answer::new(day, part, name, expected);
+            answers.insert(answer.clone());
+            recorded.push(answer);
+        }
+    }
+
+    Ok(recorded)
+}
+
+const NO_INPUT: &str = "no input";
+
+fn parse_input(store: &InputStore, day: u32, name: &str) -> Result<Box<dyn ParsedInput>, String> {
+    let solution = solution::get(day).ok_or(format!("no solution for day {day}"))?;
+    let input = store.read(day, name).map_err(|e| match e.is_missing() {
+        true => NO_INPUT.to_string(),
+        false => e.to_string(),
+    })?;
+    solution.parse(&input).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_test() {
+        let input = r#"
+# comment
+[day1.part1]
+"example1.txt" = 142
+"input.txt" = 54951
+
+[day7.part2]
+"example1.txt" = "5905"
+"#;
+        let answers: Answers = input.parse().unwrap();
+        assert_eq!(answers.len(), 3);
+        assert_eq!(
+            answers.get(1, 1, "input.txt").unwrap().expected(),
+            &Expected::plain("54951")
+        );
+        assert_eq!(
+            answers.get(7, 2, "example1.txt").unwrap().expected(),
+            &Expected::plain("5905")
+        );
+        assert_eq!(answers.to_string().parse::<Answers>(), Ok(answers));
+    }
+
+    #[test]
+    fn expected_test() {
+        let expected = Expected::hashed("1234");
+        assert!(expected.is_hashed());
+        assert!(expected.matches("1234"));
+        assert!(!expected.matches("1235"));
+        assert_eq!(expected.to_string().parse(), Ok(expected));
+
+        let expected = Expected::hashed_with_salt("abc", "");
+        assert_eq!(
+            expected.to_string(),
+            "sha256::ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_ne!(Expected::hashed("abc"), Expected::hashed("abc"));
+
+        let error = "sha256:salt:xyz".parse::<Expected>().unwrap_err();
+        assert_eq!(error.expected(), "a hex encoded SHA-256 digest");
+    }
+
+    #[test]
+    fn record_test() {
+        let mut answers: Answers = "[day9.part1]\n\"example1.txt\" = 114".parse().unwrap();
+        let store = InputStore::new("inputs");
+        let recorded = record(&mut answers, &store, Some(9), "example1.txt", true).unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].part(), 2);
+        assert!(recorded[0].expected().is_hashed());
+        assert!(recorded[0].expected().matches("2"));
+        assert_eq!(answers.len(), 2);
+
+        let recorded = record(&mut answers, &store, None, "example1.txt", false).unwrap();
+        assert!(recorded.iter().all(|a| a.day() != 9));
+        assert!(record(&mut answers, &store, None, "missing.txt", true)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let error = "[day1.part1]\n\"a.txt\" = 1\n\"b.txt\" 2"
+            .parse::<Answers>()
+            .unwrap_err();
+        assert_eq!((error.line(), error.expected()), (Some(3), "'='"));
+
+        let error = "\"a.txt\" = 1".parse::<Answers>().unwrap_err();
+        assert_eq!(error.expected(), "a '[dayN.partP]' section");
+    }
+
+    #[test]
+    fn verify_test() {
+        let answers: Answers = r#"
+[day9.part1]
+"example1.txt" = 114
+"missing.txt" = 1
+
+[day9.part2]
+"example1.txt" = 3
+
+[day12.part1]
+"example1.txt" = 1
+"#
+        .parse()
+        .unwrap();
+        let statuses: Vec<_> = verify(&answers, &InputStore::new("inputs"))
+            .into_iter()
+            .map(|v| v.status)
+            .collect();
+
+        assert_eq!(statuses[0], Status::Pass);
+        assert!(matches!(statuses[1], Status::Unknown { .. }));
+        assert_eq!(
+            statuses[2],
+            Status::Fail {
+                actual: "2".to_string()
+            }
+        );
+        assert!(matches!(statuses[3], Status::Unknown { .. }));
+    }
+
+    #[test]
+    fn answers_file_test() {
+        let answers = Answers::load(DEFAULT_ANSWERS_FILE).unwrap();
+        for verification in verify(&answers, &InputStore::from_env()) {
+            let answer = verification.answer();
+            assert!(
+                !matches!(verification.status(), Status::Fail { .. }),
+                "day {} part {} '{}': {:?}",
+                answer.day(),
+                answer.part(),
+                answer.input(),
+                verification.status()
+            );
+        }
+    }
+}
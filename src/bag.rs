@@ -0,0 +1,679 @@
+//! Inferring what's in the bag of a cube game from the grabs that were shown.
+
+use crate::cubes::{CubeSet, Game};
+use std::fmt::Display;
+
+/// Whether the cubes of a grab go back into the bag before the next one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Draw {
+    /// The puzzle's rules, every grab is taken from the full bag.
+    #[default]
+    WithReplacement,
+    /// Grabs are kept, so later ones are taken from what's left.
+    WithoutReplacement,
+}
+
+/// Every bag at least as large as `minimum`, optionally with exactly `total` cubes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feasible {
+    minimum: CubeSet,
+    total: Option<u32>,
+}
+
+impl Feasible {
+    pub fn minimum(&self) -> &CubeSet {
+        &self.minimum
+    }
+
+    pub fn total(&self) -> Option<u32> {
+        self.total
+    }
+
+    /// True if no bag fits, because `total` is smaller than the minimal bag.
+    pub fn is_empty(&self) -> bool {
+        self.total.is_some_and(|t| t < self.minimum.total())
+    }
+
+    pub fn contains(&self, bag: &CubeSet) -> bool {
+        self.minimum.is_subset_of(bag) && self.total.is_none_or(|t| bag.total() == t)
+    }
+}
+
+/// What the grabs of a single game say about its bag.
+#[derive(Debug, Clone, Copy)]
+pub struct Inference<'a> {
+    grabs: &'a [CubeSet],
+    draw: Draw,
+}
+
+impl<'a> Inference<'a> {
+    pub fn new(grabs: &'a [CubeSet], draw: Draw) -> Self {
+        Self { grabs, draw }
+    }
+
+    pub fn from_game(game: &'a Game, draw: Draw) -> Self {
+        Self::new(game.grabs(), draw)
+    }
+
+    pub fn draw(&self) -> Draw {
+        self.draw
+    }
+
+    /// The smallest bag the grabs could have come from: their union, or their sum if
+    /// they weren't put back.
+    pub fn minimal_bag(&self) -> CubeSet {
+        match self.draw {
+            Draw::WithReplacement => self
+                .grabs
+                .iter()
+                .fold(CubeSet::new(), |bag, grab| bag.union(grab)),
+            Draw::WithoutReplacement => self
+                .grabs
+                .iter()
+                .fold(CubeSet::new(), |bag, grab| bag + grab.clone()),
+        }
+    }
+
+    pub fn feasible(&self) -> Feasible {
+        Feasible {
+            minimum: self.minimal_bag(),
+            total: None,
+        }
+    }
+
+    pub fn feasible_with_total(&self, total: u32) -> Feasible {
+        Feasible {
+            minimum: self.minimal_bag(),
+            total: Some(total),
+        }
+    }
+
+    /// Natural log of the probability of seeing exactly these grabs, in order, from `bag`.
+    /// The cubes of a single grab are taken without replacement. `None` if `bag` can't
+    /// have produced the grabs.
+    pub fn log_likelihood(&self, bag: &CubeSet) -> Option<f64> {
+        let mut remaining = bag.clone();
+        let mut log_p = 0.0;
+        for grab in self.grabs {
+            if !grab.is_subset_of(&remaining) {
+                return None;
+            }
+            for (colour, count) in grab.iter() {
+                log_p += ln_binomial(remaining.get(colour), count);
+            }
+            log_p -= ln_binomial(remaining.total(), grab.total());
+            if self.draw == Draw::WithoutReplacement {
+                for (colour, count) in grab.iter() {
+                    remaining.set(colour, remaining.get(colour) - count);
+                }
+            }
+        }
+
+        Some(log_p)
+    }
+
+    /// The bag with exactly `total` cubes that makes the grabs most likely, see
+    /// [`Inference::log_likelihood`]. Cubes can be of any colour in `palette` or seen in a
+    /// grab, ties go to the colour that comes first in `palette`. `None` if `total` is
+    /// smaller than the minimal bag.
+    pub fn max_likelihood(&self, total: u32, palette: &[&str]) -> Option<CubeSet> {
+        let mut bag = self.minimal_bag();
+        let spare = total.checked_sub(bag.total())?;
+
+        // For a fixed total the likelihood is proportional to the product of C(bag[c], n)
+        // over the counts n each colour is seen with, one per grab, or a single one for
+        // the sum of the grabs if they aren't put back. Its log is concave in every colour,
+        // so adding the cube that gains the most, one at a time, finds the maximum.
+        let mut colours: Vec<&str> = palette.to_vec();
+        let grabs = self.grabs.iter().flat_map(CubeSet::colours);
+        for colour in grabs {
+            if !colours.contains(&colour) {
+                colours.push(colour);
+            }
+        }
+        let minimal = bag.clone();
+        let observed: Vec<Vec<u32>> = colours
+            .iter()
+            .map(|&colour| match self.draw {
+                Draw::WithReplacement => self.grabs.iter().map(|g| g.get(colour)).collect(),
+                Draw::WithoutReplacement => vec![minimal.get(colour)],
+            })
+            .collect();
+        let gain = |count: u32, seen: &[u32]| -> f64 {
+            let next = f64::from(count) + 1.0;
+            seen.iter()
+                .filter(|&&n| n > 0)
+                .map(|&n| (next / (next - f64::from(n))).ln())
+                .sum()
+        };
+        let gain_of = |bag: &CubeSet, i: usize| gain(bag.get(colours[i]), &observed[i]);
+        if colours.is_empty() {
+            // no colours at all, so the only bag is the empty one
+            return (spare == 0).then_some(bag);
+        }
+
+        // Every gain only shrinks as cubes are added, so taking the best one `spare` times
+        // takes the `spare` largest gains. Find the gain they're all above, up to ties, and
+        // add the cubes above it in bulk.
+        let above = |threshold: f64, i: usize| -> u32 {
+            // cubes of colour `i` that would gain more than `threshold`
+            let (mut lo, mut hi) = (0, spare);
+            while lo < hi {
+                let mid = lo + (hi - lo).div_ceil(2);
+                if gain(minimal.get(colours[i]) + mid - 1, &observed[i]) > threshold {
+                    lo = mid;
+                } else {
+                    hi = mid - 1;
+                }
+            }
+            lo
+        };
+        let count_above = |threshold: f64| -> u64 {
+            (0..colours.len())
+                .map(|i| u64::from(above(threshold, i)))
+                .sum()
+        };
+        let mut lo = 0.0;
+        let mut hi = (0..colours.len())
+            .map(|i| gain_of(&bag, i))
+            .fold(0.0, f64::max);
+        while count_above(lo) > u64::from(spare) {
+            let mid = lo + (hi - lo) / 2.0;
+            if mid <= lo || mid >= hi {
+                break;
+            }
+            if count_above(mid) > u64::from(spare) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        if count_above(lo) <= u64::from(spare) {
+            hi = lo;
+        }
+        let mut placed = 0;
+        for (i, colour) in colours.iter().enumerate() {
+            let extra = above(hi, i);
+            bag.set(*colour, bag.get(colour) + extra);
+            placed += extra;
+        }
+
+        // the rest tie at the threshold, or nothing was seen and every gain is 0
+        while placed < spare {
+            let mut best = 0;
+            let mut best_gain = f64::NEG_INFINITY;
+            for i in 0..colours.len() {
+                let g = gain_of(&bag, i);
+                if g > best_gain {
+                    best = i;
+                    best_gain = g;
+                }
+            }
+            let colour = colours[best];
+            if best_gain == 0.0 {
+                bag.set(colour, bag.get(colour) + (spare - placed));
+                break;
+            }
+            bag.set(colour, bag.get(colour) + 1);
+            placed += 1;
+        }
+
+        Some(bag)
+    }
+}
+
+/// ln C(n, k), for k <= n.
+fn ln_binomial(n: u32, k: u32) -> f64 {
+    let k = k.min(n - k);
+    (0..k)
+        .map(|i| (f64::from(n - i) / f64::from(k - i)).ln())
+        .sum()
+}
+
+/// Outcome of [`limit_for_count`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitSearch {
+    Found(CubeSet),
+    /// No limit makes exactly that many games possible.
+    NoLimit,
+    /// The budget ran out before a limit was found or ruled out.
+    GaveUp,
+}
+
+impl LimitSearch {
+    pub fn limit(&self) -> Option<&CubeSet> {
+        match self {
+            LimitSearch::Found(limit) => Some(limit),
+            _ => None,
+        }
+    }
+}
+
+/// The limit with the fewest cubes under which exactly `k` of `games` are possible.
+///
+/// The games possible under a limit are also possible under the union of their minimal
+/// bags, so only such unions need to be looked at. They're enumerated once each, growing
+/// one game at a time and skipping those that already have more than `k` games or more
+/// cubes than the best limit so far. At most `budget` unions are built, if it runs out
+/// the best limit found so far is returned, which might not be the smallest.
+pub fn limit_for_count(games: &[Game], k: usize, draw: Draw, budget: usize) -> LimitSearch {
+    let minimal: Vec<_> = games
+        .iter()
+        .map(|game| Inference::from_game(game, draw).minimal_bag())
+        .collect();
+    let mut search = LimitSearcher {
+        minimal: &minimal,
+        k,
+        budget,
+        best: None,
+        gave_up: false,
+    };
+    let limit = CubeSet::new();
+    let possible = search.possible(&limit);
+    search.visit(&limit, &possible, 0);
+
+    match (search.best, search.gave_up) {
+        (Some(limit), _) => LimitSearch::Found(limit),
+        (None, true) => LimitSearch::GaveUp,
+        (None, false) => LimitSearch::NoLimit,
+    }
+}
+
+/// Depth-first search state of [`limit_for_count`].
+struct LimitSearcher<'a> {
+    minimal: &'a [CubeSet],
+    k: usize,
+    budget: usize,
+    best: Option<CubeSet>,
+    gave_up: bool,
+}
+
+impl LimitSearcher<'_> {
+    /// Which games are possible under `limit`.
+    fn possible(&self, limit: &CubeSet) -> Vec<bool> {
+        self.minimal.iter().map(|m| m.is_subset_of(limit)).collect()
+    }
+
+    /// Looks at `limit`, then at the unions that add a game from `from` on to it.
+    fn visit(&mut self, limit: &CubeSet, possible: &[bool], from: usize) {
+        let count = possible.iter().filter(|&&p| p).count();
+        // adding games only adds cubes and possible games
+        if count > self.k
+            || self
+                .best
+                .as_ref()
+                .is_some_and(|b| b.total() <= limit.total())
+        {
+            return;
+        }
+        if count == self.k {
+            self.best = Some(limit.clone());
+            return;
+        }
+        for game in from..self.minimal.len() {
+            if possible[game] {
+                continue;
+            }
+            if self.budget == 0 {
+                self.gave_up = true;
+                return;
+            }
+            self.budget -= 1;
+            let next = limit.union(&self.minimal[game]);
+            let next_possible = self.possible(&next);
+            // a union that makes an earlier game possible is reached from that game instead
+            if next_possible[..game] != possible[..game] {
+                continue;
+            }
+            self.visit(&next, &next_possible, game + 1);
+        }
+    }
+}
+
+/// Games that share the same minimal bag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BagGroup {
+    bag: CubeSet,
+    games: Vec<u32>,
+    dominated_by: usize,
+}
+
+impl BagGroup {
+    pub fn bag(&self) -> &CubeSet {
+        &self.bag
+    }
+
+    /// Ids of the games, in input order.
+    pub fn games(&self) -> &[u32] {
+        &self.games
+    }
+
+    /// Number of other groups whose minimal bag contains this one.
+    pub fn dominated_by(&self) -> usize {
+        self.dominated_by
+    }
+
+    /// True if no other game needs a bag containing this one.
+    pub fn is_on_frontier(&self) -> bool {
+        self.dominated_by == 0
+    }
+}
+
+/// Compares the minimal bags of many games.
+///
+/// A bag dominates another if it contains it, so any limit under which the first game is
+/// possible also allows the second. The Pareto frontier are the bags nothing else dominates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dominance {
+    groups: Vec<BagGroup>,
+}
+
+impl Dominance {
+    pub fn new(games: &[Game], draw: Draw) -> Self {
+        let mut groups: Vec<BagGroup> = Vec::new();
+        for game in games {
+            let bag = Inference::from_game(game, draw).minimal_bag();
+            match groups.iter_mut().find(|g| g.bag == bag) {
+                Some(group) => group.games.push(game.id()),
+                None => groups.push(BagGroup {
+                    bag,
+                    games: vec![game.id()],
+                    dominated_by: 0,
+                }),
+            }
+        }
+        let dominated_by: Vec<_> = groups
+            .iter()
+            .map(|group| {
+                groups
+                    .iter()
+                    .filter(|other| other.bag != group.bag && group.bag.is_subset_of(&other.bag))
+                    .count()
+            })
+            .collect();
+        for (group, dominated_by) in groups.iter_mut().zip(dominated_by) {
+            group.dominated_by = dominated_by;
+        }
+
+        Self { groups }
+    }
+
+    /// Every distinct minimal bag, in the order its first game appears.
+    pub fn groups(&self) -> &[BagGroup] {
+        &self.groups
+    }
+
+    pub fn frontier(&self) -> impl Iterator<Item = &BagGroup> {
+        self.groups.iter().filter(|g| g.is_on_frontier())
+    }
+
+    /// The smallest limit under which every game is possible, the union of the frontier.
+    pub fn universal_limit(&self) -> CubeSet {
+        self.frontier()
+            .fold(CubeSet::new(), |limit, group| limit.union(&group.bag))
+    }
+}
+
+/// A table with a row per group, frontier groups marked with `*`, then the universal limit.
+impl Display for Dominance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows: Vec<[String; 4]> = self
+            .groups
+            .iter()
+            .map(|group| {
+                let games: Vec<_> = group.games.iter().map(|id| id.to_string()).collect();
+                let frontier = if group.is_on_frontier() { " *" } else { "" };
+                [
+                    format!("{}{frontier}", group.bag),
+                    group.bag.power().to_string(),
+                    group.dominated_by.to_string(),
+                    games.join(", "),
+                ]
+            })
+            .collect();
+        let header = ["minimal bag", "power", "dominated by", "games"].map(String::from);
+        let widths: Vec<_> = (0..3)
+            .map(|i| {
+                rows.iter()
+                    .chain([&header])
+                    .map(|row| row[i].len())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        for row in [&header].into_iter().chain(&rows) {
+            writeln!(
+                f,
+                "{:<w0$}  {:>w1$}  {:>w2$}  {}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+            )?;
+        }
+        write!(
+            f,
+            "smallest limit for every game: {}",
+            self.universal_limit()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::Lcg;
+
+    fn grabs(s: &str) -> Vec<CubeSet> {
+        s.split("; ").map(|g| g.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn feasible_test() {
+        let grabs = grabs("3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
+        let with = Inference::new(&grabs, Draw::WithReplacement);
+        assert_eq!(with.minimal_bag().to_string(), "6 blue, 2 green, 4 red");
+        let without = Inference::new(&grabs, Draw::WithoutReplacement);
+        assert_eq!(without.minimal_bag().to_string(), "9 blue, 4 green, 5 red");
+
+        let feasible = with.feasible_with_total(13);
+        assert!(feasible.contains(&"7 blue, 2 green, 4 red".parse().unwrap()));
+        assert!(!feasible.contains(&"6 blue, 3 green, 3 red, 1 purple".parse().unwrap()));
+        assert!(!feasible.contains(&"6 blue, 2 green, 4 red".parse().unwrap()));
+        assert!(with
+            .feasible()
+            .contains(&"6 blue, 2 green, 4 red".parse().unwrap()));
+        assert!(without.feasible_with_total(17).is_empty());
+    }
+
+    #[test]
+    fn max_likelihood_test() {
+        let palette = ["red", "green", "blue"];
+        for draw in [Draw::WithReplacement, Draw::WithoutReplacement] {
+            let grabs = grabs("3 red, 1 blue; 2 red; 1 red, 1 blue");
+            let inference = Inference::new(&grabs, draw);
+            let best = inference.max_likelihood(12, &palette).unwrap();
+            assert_eq!(best.total(), 12);
+
+            // compare with every bag of 12 cubes
+            let best_log_p = inference.log_likelihood(&best).unwrap();
+            for red in 0..=12 {
+                for blue in 0..=12 - red {
+                    let bag = CubeSet::new()
+                        .with("red", red)
+                        .with("green", 12 - red - blue)
+                        .with("blue", blue);
+                    let log_p = inference.log_likelihood(&bag);
+                    assert!(
+                        log_p.is_none_or(|p| p <= best_log_p + 1e-9),
+                        "{draw:?}, {bag}"
+                    );
+                }
+            }
+        }
+
+        // one cube at a time, each where it makes the grabs most likely
+        let seen = grabs("5 red, 1 blue, 2 green; 2 red, 4 blue; 1 green");
+        for draw in [Draw::WithReplacement, Draw::WithoutReplacement] {
+            let inference = Inference::new(&seen, draw);
+            let mut bag = inference.minimal_bag();
+            for total in bag.total()..300 {
+                let best = inference.max_likelihood(total, &palette).unwrap();
+                let log_p = |bag: &CubeSet| inference.log_likelihood(bag).unwrap();
+                assert!(
+                    (log_p(&best) - log_p(&bag)).abs() < 1e-9,
+                    "{draw:?}, {total}"
+                );
+                bag = palette
+                    .iter()
+                    .map(|colour| bag.clone().with(*colour, bag.get(colour) + 1))
+                    .max_by(|a, b| log_p(a).total_cmp(&log_p(b)))
+                    .unwrap();
+            }
+            let huge = inference.max_likelihood(u32::MAX, &palette).unwrap();
+            assert_eq!(huge.total(), u32::MAX);
+            assert_eq!(huge.get("purple"), 0);
+        }
+
+        let grabs = grabs("3 red, 1 blue");
+        let inference = Inference::new(&grabs, Draw::WithReplacement);
+        assert_eq!(inference.max_likelihood(3, &[]), None);
+        assert_eq!(
+            inference
+                .max_likelihood(u32::MAX, &["green"])
+                .unwrap()
+                .get("green"),
+            0
+        );
+        let nothing = Inference::new(&[], Draw::WithReplacement);
+        assert_eq!(
+            nothing.max_likelihood(5, &palette).unwrap().to_string(),
+            "5 red"
+        );
+        assert_eq!(
+            inference.max_likelihood(8, &[]).unwrap().to_string(),
+            "2 blue, 6 red"
+        );
+    }
+
+    #[test]
+    fn limit_for_count_test() {
+        let games: Vec<Game> = [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+            "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
+            "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+        ]
+        .iter()
+        .map(|g| g.parse().unwrap())
+        .collect();
+
+        for k in 0..=games.len() {
+            let search = limit_for_count(&games, k, Draw::WithReplacement, 1000);
+            let limit = search.limit().unwrap();
+            let possible = games.iter().filter(|g| g.is_possible_with(limit)).count();
+            assert_eq!(possible, k);
+        }
+        let search = limit_for_count(&games, 1, Draw::WithReplacement, 1000);
+        assert_eq!(
+            search.limit().unwrap().to_string(),
+            "4 blue, 3 green, 1 red"
+        );
+        assert_eq!(
+            limit_for_count(&games, 1, Draw::WithReplacement, 0),
+            LimitSearch::GaveUp
+        );
+
+        // the same minimal bag twice, so they're possible together or not at all
+        let twins: Vec<Game> = ["Game 1: 1 red", "Game 2: 1 red", "Game 3: 2 blue"]
+            .iter()
+            .map(|g| g.parse().unwrap())
+            .collect();
+        assert_eq!(
+            limit_for_count(&twins, 1, Draw::WithReplacement, 1000),
+            LimitSearch::Found(CubeSet::new().with("blue", 2))
+        );
+        assert_eq!(
+            limit_for_count(&twins, 2, Draw::WithReplacement, 1000),
+            LimitSearch::Found(CubeSet::new().with("red", 1))
+        );
+        let without_blue = &twins[..2];
+        assert_eq!(
+            limit_for_count(without_blue, 1, Draw::WithReplacement, 1000),
+            LimitSearch::NoLimit
+        );
+    }
+
+    #[test]
+    fn limit_for_count_random_test() {
+        let mut lcg = Lcg::new(0xba6);
+        let mut random = |n: u32| lcg.below(u64::from(n)) as u32;
+        for _ in 0..50 {
+            let games: Vec<_> = (1..=random(7) + 1)
+                .map(|id| {
+                    let grab = CubeSet::new()
+                        .with("red", random(4))
+                        .with("green", random(4))
+                        .with("blue", random(3));
+                    Game::new(id, vec![grab])
+                })
+                .collect();
+            let minimal: Vec<_> = games.iter().map(Game::minimum_bag).collect();
+            for k in 0..=games.len() {
+                // every subset of games, and the smallest union that allows exactly `k`
+                let smallest = (0_u32..1 << games.len())
+                    .map(|subset| {
+                        (0..games.len())
+                            .filter(|i| subset & 1 << i != 0)
+                            .fold(CubeSet::new(), |limit, i| limit.union(&minimal[i]))
+                    })
+                    .filter(|limit| minimal.iter().filter(|m| m.is_subset_of(limit)).count() == k)
+                    .map(|limit| limit.total())
+                    .min();
+                let search = limit_for_count(&games, k, Draw::WithReplacement, usize::MAX);
+                assert_eq!(search.limit().map(CubeSet::total), smallest, "{k}");
+                assert_ne!(search, LimitSearch::GaveUp);
+            }
+        }
+    }
+
+    #[test]
+    fn dominance_test() {
+        let games: Vec<Game> = [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green",
+            "Game 2: 2 green, 1 blue",
+            "Game 3: 4 red, 3 blue; 2 green",
+            "Game 4: 5 red",
+            "Game 5: 1 red; 1 blue",
+        ]
+        .iter()
+        .map(|g| g.parse().unwrap())
+        .collect();
+        let dominance = Dominance::new(&games, Draw::WithReplacement);
+
+        let groups: Vec<_> = dominance
+            .groups()
+            .iter()
+            .map(|g| (g.games(), g.dominated_by()))
+            .collect();
+        assert_eq!(groups, [(&[1, 3][..], 0), (&[2], 1), (&[4], 0), (&[5], 1)]);
+        assert_eq!(dominance.frontier().count(), 2);
+        let limit = dominance.universal_limit();
+        assert_eq!(limit.to_string(), "3 blue, 2 green, 5 red");
+        assert!(games.iter().all(|g| g.is_possible_with(&limit)));
+        assert_eq!(
+            dominance.to_string(),
+            "\
+minimal bag               power  dominated by  games
+3 blue, 2 green, 4 red *     24             0  1, 3
+1 blue, 2 green               2             1  2
+5 red *                       5             0  4
+1 blue, 1 red                 1             1  5
+smallest limit for every game: 3 blue, 2 green, 5 red"
+        );
+    }
+}
@@ -0,0 +1,448 @@
+use crate::{
+    error::ParseError,
+    json::Json,
+    solution::{DynSolution, ParsedInput},
+};
+use std::{
+    fmt::Display,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+/// How many times every phase is run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchConfig {
+    warmup: usize,
+    samples: usize,
+}
+
+impl BenchConfig {
+    pub fn new(warmup: usize, samples: usize) -> Self {
+        Self {
+            warmup,
+            samples: samples.max(1),
+        }
+    }
+
+    pub fn warmup(&self) -> usize {
+        self.warmup
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self::new(3, 20)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 3] = [Phase::Parse, Phase::Part1, Phase::Part2];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::Part1 => "part1",
+            Phase::Part2 => "part2",
+        }
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// Summary of the samples of a single phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    min: Duration,
+    median: Duration,
+    p95: Duration,
+}
+
+impl Stats {
+    pub fn new(min: Duration, median: Duration, p95: Duration) -> Self {
+        Self { min, median, p95 }
+    }
+
+    /// Returns `None` if there are no samples, percentiles use the nearest rank.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let rank = |p: usize| sorted[(sorted.len() * p).div_ceil(100).max(1) - 1];
+
+        Some(Self {
+            min: *sorted.first()?,
+            median: rank(50),
+            p95: rank(95),
+        })
+    }
+
+    pub fn min(&self) -> Duration {
+        self.min
+    }
+
+    pub fn median(&self) -> Duration {
+        self.median
+    }
+
+    pub fn p95(&self) -> Duration {
+        self.p95
+    }
+
+    fn to_json(self) -> Json {
+        let nanos = |d: Duration| Json::from(d.as_nanos() as u64);
+        Json::Object(vec![
+            ("min_ns".to_string(), nanos(self.min)),
+            ("median_ns".to_string(), nanos(self.median)),
+            ("p95_ns".to_string(), nanos(self.p95)),
+        ])
+    }
+
+    fn from_json(json: &Json, path: &str) -> Result<Self, ParseError> {
+        let nanos = |key| {
+            field(json, path, key, "a number of nanoseconds", Json::as_u64)
+                .map(Duration::from_nanos)
+        };
+        Ok(Self::new(
+            nanos("min_ns")?,
+            nanos("median_ns")?,
+            nanos("p95_ns")?,
+        ))
+    }
+}
+
+/// Benchmark results for one day and input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayBench {
+    day: u32,
+    input: String,
+    parse: Stats,
+    part1: Stats,
+    part2: Stats,
+}
+
+impl DayBench {
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn stats(&self, phase: Phase) -> Stats {
+        match phase {
+            Phase::Parse => self.parse,
+            Phase::Part1 => self.part1,
+            Phase::Part2 => self.part2,
+        }
+    }
+
+    fn to_json(&self) -> Json {
+        let mut fields = vec![
+            ("day".to_string(), self.day.into()),
+            ("input".to_string(), self.input.as_str().into()),
+        ];
+        for phase in Phase::ALL {
+            fields.push((phase.name().to_string(), self.stats(phase).to_json()));
+        }
+        Json::Object(fields)
+    }
+
+    fn from_json(json: &Json, path: &str) -> Result<Self, ParseError> {
+        let stats = |phase: Phase| {
+            let stats = field(json, path, phase.name(), "stats", Some)?;
+            Stats::from_json(stats, &format!("{path}.{}", phase.name()))
+        };
+        let day = |v: &Json| v.as_u64()?.try_into().ok();
+        Ok(Self {
+            day: field(json, path, "day", "a day number", day)?,
+            input: field(json, path, "input", "an input name", Json::as_str)?.to_string(),
+            parse: stats(Phase::Parse)?,
+            part1: stats(Phase::Part1)?,
+            part2: stats(Phase::Part2)?,
+        })
+    }
+}
+
+/// Reads `key` of the object at `path` with `read`. Errors name the key by its path and
+/// only show scalar values, not whole objects.
+fn field<'a, T>(
+    json: &'a Json,
+    path: &str,
+    key: &str,
+    expected: &str,
+    read: impl FnOnce(&'a Json) -> Option<T>,
+) -> Result<T, ParseError> {
+    let value = json.get(key);
+    value.and_then(read).ok_or_else(|| {
+        let found = match value {
+            None => String::new(),
+            Some(Json::Object(_)) => "{...}".to_string(),
+            Some(Json::Array(_)) => "[...]".to_string(),
+            Some(value) => value.to_string(),
+        };
+        let path = if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        };
+        ParseError::new(found, format!("{expected} at '{path}'"))
+    })
+}
+
+/// Times `f` `samples` times after running it `warmup` times.
+fn sample<T>(config: BenchConfig, mut f: impl FnMut() -> T) -> Stats {
+    for _ in 0..config.warmup {
+        black_box(f());
+    }
+    let samples: Vec<_> = (0..config.samples)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+
+    Stats::from_samples(&samples).expect("there is at least one sample")
+}
+
+/// Benchmarks parsing and both parts of `solution` on `input`.
+pub fn bench_day(
+    solution: &dyn DynSolution,
+    name: &str,
+    input: &str,
+    config: BenchConfig,
+) -> Result<DayBench, ParseError> {
+    let parsed: Box<dyn ParsedInput> = solution.parse(input)?;
+    let parse = sample(config, || solution.parse(input));
+    let part1 = sample(config, || parsed.part1());
+    let part2 = sample(config, || parsed.part2());
+
+    Ok(DayBench {
+        day: solution.day(),
+        input: name.to_string(),
+        parse,
+        part1,
+        part2,
+    })
+}
+
+/// A set of benchmark results, stored as JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BenchReport {
+    benches: Vec<DayBench>,
+}
+
+impl BenchReport {
+    pub fn new(benches: Vec<DayBench>) -> Self {
+        Self { benches }
+    }
+
+    pub fn benches(&self) -> &[DayBench] {
+        &self.benches
+    }
+
+    pub fn get(&self, day: u32, input: &str) -> Option<&DayBench> {
+        self.benches
+            .iter()
+            .find(|b| b.day == day && b.input == input)
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![(
+            "benches".to_string(),
+            Json::Array(self.benches.iter().map(DayBench::to_json).collect()),
+        )])
+    }
+
+    pub fn from_json(json: &Json) -> Result<Self, ParseError> {
+        let benches = field(json, "", "benches", "an array", Json::as_array)?
+            .iter()
+            .enumerate()
+            .map(|(i, b)| DayBench::from_json(b, &format!("benches[{i}]")))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { benches })
+    }
+
+    /// Compares medians against `baseline`, for days and inputs present in both.
+    pub fn compare(&self, baseline: &BenchReport) -> Vec<Comparison> {
+        self.benches
+            .iter()
+            .filter_map(|current| Some((current, baseline.get(current.day, &current.input)?)))
+            .flat_map(|(current, baseline)| {
+                Phase::ALL.map(|phase| Comparison {
+                    day: current.day,
+                    input: current.input.clone(),
+                    phase,
+                    baseline: baseline.stats(phase).median,
+                    current: current.stats(phase).median,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Median of one phase compared to the baseline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    day: u32,
+    input: String,
+    phase: Phase,
+    baseline: Duration,
+    current: Duration,
+}
+
+impl Comparison {
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn baseline(&self) -> Duration {
+        self.baseline
+    }
+
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Relative change, `0.1` is 10% slower than the baseline.
+    pub fn change(&self) -> f64 {
+        let baseline = self.baseline.as_secs_f64();
+        if baseline == 0.0 {
+            return 0.0;
+        }
+        self.current.as_secs_f64() / baseline - 1.0
+    }
+
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        self.change() > threshold
+    }
+}
+
+/// Small linear congruential generator, so random inputs are the same on every run.
+#[derive(Debug, Clone)]
+pub struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// The next number in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.state >> 33) % n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn lcg_test() {
+        let mut lcg = Lcg::new(0x2023);
+        let numbers: Vec<_> = (0..1000).map(|_| lcg.below(10)).collect();
+        assert!(numbers.iter().all(|&n| n < 10));
+        assert!((0..10).all(|n| numbers.contains(&n)));
+        let mut again = Lcg::new(0x2023);
+        assert!(numbers.iter().all(|&n| again.below(10) == n));
+    }
+
+    #[test]
+    fn stats_test() {
+        let samples: Vec<_> = (1..=20).rev().map(ms).collect();
+        let stats = Stats::from_samples(&samples).unwrap();
+        assert_eq!(stats, Stats::new(ms(1), ms(10), ms(19)));
+        assert_eq!(
+            Stats::from_samples(&[ms(4)]),
+            Some(Stats::new(ms(4), ms(4), ms(4)))
+        );
+        assert_eq!(Stats::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn report_test() {
+        let input = "0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45";
+        let bench = bench_day(
+            solution::get(9).unwrap(),
+            "example1.txt",
+            input,
+            BenchConfig::new(0, 3),
+        )
+        .unwrap();
+        let report = BenchReport::new(vec![bench]);
+        let json = report.to_json().to_string().parse().unwrap();
+        assert_eq!(BenchReport::from_json(&json), Ok(report));
+
+        let error = BenchReport::from_json(&Json::Null).unwrap_err();
+        assert_eq!(
+            (error.text(), error.expected()),
+            ("", "an array at 'benches'")
+        );
+        let json: Json = r#"{"benches": [{"day": 9, "input": "a", "parse": {"min_ns": "1ms"}}]}"#
+            .parse()
+            .unwrap();
+        let error = BenchReport::from_json(&json).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected a number of nanoseconds at 'benches[0].parse.min_ns', found '\"1ms\"'"
+        );
+    }
+
+    #[test]
+    fn compare_test() {
+        let bench = |day, median| DayBench {
+            day,
+            input: "input.txt".to_string(),
+            parse: Stats::new(ms(1), ms(1), ms(1)),
+            part1: Stats::new(ms(1), ms(median), ms(median)),
+            part2: Stats::new(ms(1), ms(10), ms(10)),
+        };
+        let baseline = BenchReport::new(vec![bench(1, 10), bench(2, 10)]);
+        let current = BenchReport::new(vec![bench(1, 15), bench(3, 10)]);
+
+        let comparisons = current.compare(&baseline);
+        assert_eq!(comparisons.len(), 3);
+        let regressions: Vec<_> = comparisons
+            .iter()
+            .filter(|c| c.is_regression(0.1))
+            .map(|c| (c.day(), c.phase()))
+            .collect();
+        assert_eq!(regressions, [(1, Phase::Part1)]);
+        assert!((comparisons[1].change() - 0.5).abs() < 1e-9);
+    }
+}
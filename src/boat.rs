@@ -1,3 +1,10 @@
+use crate::{
+    error::ParseError,
+    parse::{input_lines, InputLine},
+};
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Race {
     time: u64,
     record: u64,
@@ -22,6 +29,136 @@ impl Race {
     }
 }
 
+/// Parses a sheet as a single race, ignoring the spaces between digits.
+impl FromStr for Race {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (time_line, distance_line) = time_and_distance(s)?;
+
+        let time = parse_kerned_number(time_line, "Time:")?;
+        let record = parse_kerned_number(distance_line, "Distance:")?;
+
+        Ok(Race::new(time, record))
+    }
+}
+
+impl Display for Race {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Time: {}\nDistance: {}", self.time, self.record)
+    }
+}
+
+/// A sheet of races, one per column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Races(Vec<Race>);
+
+impl Races {
+    pub fn new(races: Vec<Race>) -> Self {
+        Self(races)
+    }
+
+    pub fn races(&self) -> &[Race] {
+        &self.0
+    }
+}
+
+impl FromStr for Races {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (time_line, distance_line) = time_and_distance(s)?;
+
+        let times = parse_numbers(time_line, "Time:")?;
+        let records = parse_numbers(distance_line, "Distance:")?;
+
+        if times.len() != records.len() {
+            return Err(distance_line.error(
+                distance_line.text(),
+                &format!("{} distances, one per race", times.len()),
+            ));
+        }
+
+        Ok(Self(
+            times
+                .into_iter()
+                .zip(records)
+                .map(|(time, record)| Race::new(time, record))
+                .collect(),
+        ))
+    }
+}
+
+impl Display for Races {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let times: Vec<_> = self.0.iter().map(|r| r.time.to_string()).collect();
+        let records: Vec<_> = self.0.iter().map(|r| r.record.to_string()).collect();
+        write!(
+            f,
+            "Time: {}\nDistance: {}",
+            times.join(" "),
+            records.join(" ")
+        )
+    }
+}
+
+fn time_and_distance(input: &str) -> Result<(InputLine<'_>, InputLine<'_>), ParseError> {
+    let mut lines = input_lines(input);
+    let time = lines
+        .next()
+        .ok_or_else(|| ParseError::missing_line(1, "'Time:'"))?;
+    let distance = lines
+        .next()
+        .ok_or_else(|| ParseError::missing_line(2, "'Distance:'"))?;
+    if let Some(extra) = lines.find(|l| !l.text().trim().is_empty()) {
+        return Err(extra.error(extra.text(), "end of input"));
+    }
+
+    Ok((time, distance))
+}
+
+fn parse_numbers(line: InputLine, prefix: &str) -> Result<Vec<u64>, ParseError> {
+    line.strip_prefix(line.text(), prefix)?
+        .split_whitespace()
+        .map(|p| line.parse(p, "a number"))
+        .collect()
+}
+
+fn parse_kerned_number(line: InputLine, prefix: &str) -> Result<u64, ParseError> {
+    let numbers = line.strip_prefix(line.text(), prefix)?;
+    let mut digits = String::new();
+    for part in numbers.split_whitespace() {
+        line.parse::<u64>(part, "a number")?;
+        digits.push_str(part);
+    }
+
+    digits
+        .parse()
+        .map_err(|_| line.error(numbers.trim(), "a number that fits in 64 bits"))
+}
+
 fn get_distance(speed: u64, time: u64) -> u64 {
     speed * time
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Time:      7  15   30
+Distance:  9  40  200";
+
+    #[test]
+    fn round_trip_test() {
+        let races: Races = EXAMPLE.parse().unwrap();
+        assert_eq!(
+            races.races(),
+            [Race::new(7, 9), Race::new(15, 40), Race::new(30, 200)]
+        );
+        assert_eq!(races.to_string().parse::<Races>(), Ok(races));
+
+        let race: Race = EXAMPLE.parse().unwrap();
+        assert_eq!(race, Race::new(71530, 940200));
+        assert_eq!(race.to_string().parse::<Race>(), Ok(race));
+    }
+}
@@ -1,49 +1,568 @@
-static NUMBERS: [&str; 18] = [
-    "1", "2", "3", "4", "5", "6", "7", "8", "9", "one", "two", "three", "four", "five", "six",
-    "seven", "eight", "nine",
+use crate::scanner::{Match, Scanner};
+use std::{
+    fmt::Display,
+    io::{self, BufRead},
+    sync::OnceLock,
+};
+
+const DIGITS: [(&str, u64); 9] = [
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
 ];
 
-pub fn calibration_value(line: &str, include_words: bool) -> Option<u32> {
-    dbg!(line);
-    let mut first_occurrences = Vec::with_capacity(line.len());
-    let mut last_occurrences = Vec::with_capacity(line.len());
-    for number in NUMBERS {
-        if !include_words && is_word(number) {
-            continue;
+const ENGLISH_WORDS: [(&str, u64); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+/// Which tokens of a line make up its value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// The first and the last token, which may be the same one.
+    #[default]
+    FirstLast,
+    /// Every token ordered by where it starts, overlapping ones included, so `eightwo` is 8 and 2.
+    All,
+    /// Tokens found left to right, taking the longest at each position and skipping what it covers.
+    Leftmost,
+}
+
+/// Tokens and their values, plus how to turn the tokens of a line into a single number.
+///
+/// The value of a line is built like a number in `base`, each token being a digit:
+/// with the defaults `two1nine` is `2 * 10 + 9`.
+#[derive(Debug, Clone)]
+pub struct CalibrationVocabulary {
+    tokens: Vec<(String, u64)>,
+    selection: Selection,
+    base: u64,
+    scanner: Scanner,
+}
+
+impl CalibrationVocabulary {
+    pub fn builder() -> CalibrationVocabularyBuilder {
+        CalibrationVocabularyBuilder::default()
+    }
+
+    /// `1` to `9`, the vocabulary of day 1 part 1.
+    pub fn digits() -> Self {
+        Self::builder().digits().build().expect("digits are valid")
+    }
+
+    /// `1` to `9` and `one` to `nine`, the vocabulary of day 1 part 2.
+    pub fn english() -> Self {
+        Self::builder()
+            .digits()
+            .english_words()
+            .build()
+            .expect("digits and words are valid")
+    }
+
+    pub fn tokens(&self) -> &[(String, u64)] {
+        &self.tokens
+    }
+
+    pub fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    /// Returns `None` if the line has no tokens, or if its value doesn't fit in a `u64`.
+    pub fn value(&self, line: &str) -> Option<u64> {
+        self.value_bytes(line.as_bytes())
+    }
+
+    fn value_bytes(&self, line: &[u8]) -> Option<u64> {
+        let token_value = |m: Match| self.tokens[m.pattern()].1;
+        let compose = |acc: u64, value: u64| acc.checked_mul(self.base)?.checked_add(value);
+        match self.selection {
+            Selection::FirstLast => {
+                let first = token_value(self.scanner.first(line)?);
+                let last = token_value(self.scanner.last(line)?);
+                compose(first, last)
+            }
+            Selection::All | Selection::Leftmost => {
+                compose_all(self.chosen(line).into_iter().map(token_value), compose)
+            }
+        }
+    }
+
+    /// Every match ordered by where it starts, the longest first.
+    fn all_matches(&self, line: &[u8]) -> Vec<Match> {
+        let mut matches: Vec<_> = self.scanner.find_overlapping(line).collect();
+        matches.sort_by_key(|m| (m.start(), std::cmp::Reverse(m.end())));
+        matches
+    }
+
+    /// The matches that make up the value of `line`, in order.
+    fn chosen(&self, line: &[u8]) -> Vec<Match> {
+        match self.selection {
+            Selection::FirstLast => self
+                .scanner
+                .first(line)
+                .into_iter()
+                .chain(self.scanner.last(line))
+                .collect(),
+            Selection::All => self.all_matches(line),
+            Selection::Leftmost => {
+                let mut chosen = Vec::new();
+                let mut at = 0;
+                while let Some(m) = self.scanner.first(&line[at..]) {
+                    chosen.push(m.offset(at));
+                    at += m.end();
+                }
+                chosen
+            }
+        }
+    }
+
+    /// Lists every token in `line`, which of them make up its value and anything suspicious.
+    pub fn explain(&self, line: &str) -> Explanation {
+        let bytes = line.as_bytes();
+        let all = self.all_matches(bytes);
+        let chosen = self.chosen(bytes);
+        let matches: Vec<_> = all
+            .iter()
+            .map(|m| TokenMatch {
+                token: self.tokens[m.pattern()].0.clone(),
+                value: self.tokens[m.pattern()].1,
+                start: m.start(),
+                end: m.end(),
+                chosen: chosen.contains(m),
+            })
+            .collect();
+
+        let mut warnings = Vec::new();
+        let value = self.value(line);
+        if all.is_empty() {
+            warnings.push(Warning::NoTokens);
+        } else if value.is_none() {
+            warnings.push(Warning::Overflow);
+        }
+        for (i, a) in all.iter().enumerate() {
+            for b in all[i + 1..].iter().take_while(|b| b.start() < a.end()) {
+                warnings.push(Warning::Overlap {
+                    first: (a.start(), a.end()),
+                    second: (b.start(), b.end()),
+                });
+            }
         }
-        if let Some(left) = line.find(number) {
-            first_occurrences.push((left, value(number)));
+        if self.selection == Selection::FirstLast && chosen.len() == 2 && chosen[0] == chosen[1] {
+            warnings.push(Warning::SingleToken {
+                span: (chosen[0].start(), chosen[0].end()),
+            });
         }
-        if let Some(right) = line.rfind(number) {
-            last_occurrences.push((right, value(number)));
+
+        Explanation {
+            line: line.to_string(),
+            matches,
+            value,
+            warnings,
         }
     }
 
-    let (_, left) = first_occurrences.iter().min_by_key(|(idx, _)| idx)?;
-    let (_, right) = last_occurrences.iter().max_by_key(|(idx, _)| idx)?;
+    /// Sums the values of every non-empty line, reusing a single line buffer.
+    ///
+    /// A line without a value is reported as [`io::ErrorKind::InvalidData`].
+    pub fn sum(&self, mut reader: impl BufRead) -> io::Result<u64> {
+        let mut line = Vec::new();
+        let mut sum: u64 = 0;
+        let mut number = 0;
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                return Ok(sum);
+            }
+            number += 1;
+            let text = line.trim_ascii_end();
+            if text.is_empty() {
+                continue;
+            }
+            let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+            let value = self
+                .value_bytes(text)
+                .ok_or_else(|| invalid(format!("line {number} has no value")))?;
+            sum = sum
+                .checked_add(value)
+                .ok_or_else(|| invalid(format!("sum overflows at line {number}")))?;
+        }
+    }
+}
 
-    Some(10 * left + right)
+/// A token found in a line, `start..end` is its byte span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMatch {
+    token: String,
+    value: u64,
+    start: usize,
+    end: usize,
+    chosen: bool,
 }
 
-fn value(s: &str) -> u32 {
-    if !is_word(s) {
-        s.parse().unwrap()
-    } else {
-        match s {
-            "one" => 1,
-            "two" => 2,
-            "three" => 3,
-            "four" => 4,
-            "five" => 5,
-            "six" => 6,
-            "seven" => 7,
-            "eight" => 8,
-            "nine" => 9,
-            _ => unreachable!(),
+impl TokenMatch {
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Whether this token is part of the value of the line.
+    pub fn is_chosen(&self) -> bool {
+        self.chosen
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    NoTokens,
+    /// The value doesn't fit in a `u64`.
+    Overflow,
+    /// Two tokens share bytes, like `eight` and `two` in `eightwo`.
+    Overlap {
+        first: (usize, usize),
+        second: (usize, usize),
+    },
+    /// The only token is used as both the first and the last one.
+    SingleToken {
+        span: (usize, usize),
+    },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::NoTokens => write!(f, "no tokens"),
+            Warning::Overflow => write!(f, "the value doesn't fit in 64 bits"),
+            Warning::Overlap { first, second } => write!(
+                f,
+                "tokens at {}..{} and {}..{} overlap",
+                first.0, first.1, second.0, second.1
+            ),
+            Warning::SingleToken { span } => write!(
+                f,
+                "the token at {}..{} is both first and last",
+                span.0, span.1
+            ),
         }
     }
 }
 
-fn is_word(s: &str) -> bool {
-    s.len() > 1
+/// Result of [`CalibrationVocabulary::explain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    line: String,
+    matches: Vec<TokenMatch>,
+    value: Option<u64>,
+    warnings: Vec<Warning>,
+}
+
+impl Explanation {
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// Every token in the line, overlapping ones included, ordered by where they start.
+    pub fn matches(&self) -> &[TokenMatch] {
+        &self.matches
+    }
+
+    pub fn value(&self) -> Option<u64> {
+        self.value
+    }
+
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+}
+
+/// Writes the line and its value, then a line per token and per warning.
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.value {
+            Some(value) => writeln!(f, "{} = {value}", self.line)?,
+            None => writeln!(f, "{} = no value", self.line)?,
+        }
+        for m in &self.matches {
+            let chosen = if m.chosen { " *" } else { "" };
+            writeln!(
+                f,
+                "    {:>3}..{:<3} {} = {}{chosen}",
+                m.start, m.end, m.token, m.value
+            )?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "    warning: {warning}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn compose_all(
+    mut values: impl Iterator<Item = u64>,
+    compose: impl Fn(u64, u64) -> Option<u64>,
+) -> Option<u64> {
+    let first = values.next()?;
+    values.try_fold(first, compose)
+}
+
+#[derive(Debug, Clone)]
+pub struct CalibrationVocabularyBuilder {
+    tokens: Vec<(String, u64)>,
+    selection: Selection,
+    base: u64,
+}
+
+impl Default for CalibrationVocabularyBuilder {
+    fn default() -> Self {
+        Self {
+            tokens: Vec::new(),
+            selection: Selection::FirstLast,
+            base: 10,
+        }
+    }
+}
+
+impl CalibrationVocabularyBuilder {
+    /// Adds a token, replacing the value of a token that was added before.
+    pub fn token(mut self, token: impl Into<String>, value: u64) -> Self {
+        let token = token.into();
+        match self.tokens.iter_mut().find(|(t, _)| *t == token) {
+            Some(existing) => existing.1 = value,
+            None => self.tokens.push((token, value)),
+        }
+        self
+    }
+
+    pub fn tokens<T: Into<String>>(self, tokens: impl IntoIterator<Item = (T, u64)>) -> Self {
+        tokens
+            .into_iter()
+            .fold(self, |builder, (token, value)| builder.token(token, value))
+    }
+
+    /// `1` to `9`, zero is left out like in the puzzle.
+    pub fn digits(self) -> Self {
+        self.tokens(DIGITS)
+    }
+
+    pub fn english_words(self) -> Self {
+        self.tokens(ENGLISH_WORDS)
+    }
+
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn base(mut self, base: u64) -> Self {
+        self.base = base;
+        self
+    }
+
+    pub fn build(self) -> Result<CalibrationVocabulary, VocabularyError> {
+        if self.tokens.is_empty() {
+            return Err(VocabularyError::NoTokens);
+        }
+        if self.tokens.iter().any(|(t, _)| t.is_empty()) {
+            return Err(VocabularyError::EmptyToken);
+        }
+        if self.base < 2 {
+            return Err(VocabularyError::Base(self.base));
+        }
+
+        Ok(CalibrationVocabulary {
+            scanner: Scanner::new(self.tokens.iter().map(|(t, _)| t)),
+            tokens: self.tokens,
+            selection: self.selection,
+            base: self.base,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VocabularyError {
+    NoTokens,
+    EmptyToken,
+    Base(u64),
+}
+
+impl Display for VocabularyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VocabularyError::NoTokens => write!(f, "a vocabulary needs at least one token"),
+            VocabularyError::EmptyToken => write!(f, "tokens can't be empty"),
+            VocabularyError::Base(base) => write!(f, "the base must be at least 2, got {base}"),
+        }
+    }
+}
+
+impl std::error::Error for VocabularyError {}
+
+fn default_vocabulary(include_words: bool) -> &'static CalibrationVocabulary {
+    static DIGITS: OnceLock<CalibrationVocabulary> = OnceLock::new();
+    static ENGLISH: OnceLock<CalibrationVocabulary> = OnceLock::new();
+    if include_words {
+        ENGLISH.get_or_init(CalibrationVocabulary::english)
+    } else {
+        DIGITS.get_or_init(CalibrationVocabulary::digits)
+    }
+}
+
+pub fn calibration_value(line: &str, include_words: bool) -> Option<u32> {
+    let value = default_vocabulary(include_words).value(line)?;
+    Some(value.try_into().expect("two digits fit in a u32"))
+}
+
+/// Sums the calibration values of every non-empty line, see [`CalibrationVocabulary::sum`].
+pub fn calibration_sum(reader: impl BufRead, include_words: bool) -> io::Result<u64> {
+    default_vocabulary(include_words).sum(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calibration_value_test() {
+        assert_eq!(calibration_value("treb7uchet", false), Some(77));
+        assert_eq!(calibration_value("eightwothree", false), None);
+        assert_eq!(calibration_value("eightwothree", true), Some(83));
+        assert_eq!(calibration_value("xtwone3four", true), Some(24));
+        assert_eq!(calibration_value("oneight", true), Some(18));
+        assert_eq!(calibration_value("9eightwo", true), Some(92));
+    }
+
+    #[test]
+    fn calibration_sum_test() {
+        let input = "two1nine\r\neightwothree\n\nabcone2threexyz\n";
+        assert_eq!(
+            calibration_sum(input.as_bytes(), true).unwrap(),
+            29 + 83 + 13
+        );
+
+        let error = calibration_sum("1\nabc\n".as_bytes(), false).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "line 2 has no value");
+    }
+
+    #[test]
+    fn vocabulary_test() {
+        let french = CalibrationVocabulary::builder()
+            .digits()
+            .tokens([
+                ("zéro", 0),
+                ("un", 1),
+                ("deux", 2),
+                ("trois", 3),
+                ("dix", 10),
+            ])
+            .build()
+            .unwrap();
+        assert_eq!(french.value("deuxabc3"), Some(23));
+        assert_eq!(french.value("dixun"), Some(101));
+        assert_eq!(french.value("zéro"), Some(0));
+
+        let roman = CalibrationVocabulary::builder()
+            .tokens([("I", 1), ("V", 5), ("X", 10), ("IV", 4), ("IX", 9)])
+            .selection(Selection::Leftmost)
+            .base(100)
+            .build()
+            .unwrap();
+        assert_eq!(roman.value("XIV"), Some(10_04));
+        assert_eq!(roman.value("IXI"), Some(9_01));
+
+        let all = CalibrationVocabulary::builder()
+            .digits()
+            .english_words()
+            .selection(Selection::All)
+            .build()
+            .unwrap();
+        assert_eq!(all.value("eightwo3"), Some(823));
+        assert_eq!(all.value("a1b"), Some(1));
+        assert_eq!(all.value("9".repeat(25).as_str()), None);
+    }
+
+    #[test]
+    fn explain_test() {
+        let explanation = CalibrationVocabulary::english().explain("xeightwo3");
+        let spans: Vec<_> = explanation
+            .matches()
+            .iter()
+            .map(|m| (m.token(), m.start(), m.end(), m.is_chosen()))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("eight", 1, 6, true),
+                ("two", 5, 8, false),
+                ("3", 8, 9, true)
+            ]
+        );
+        assert_eq!(explanation.value(), Some(83));
+        assert_eq!(
+            explanation.warnings(),
+            [Warning::Overlap {
+                first: (1, 6),
+                second: (5, 8)
+            }]
+        );
+
+        let explanation = CalibrationVocabulary::digits().explain("treb7uchet");
+        assert_eq!(
+            explanation.warnings(),
+            [Warning::SingleToken { span: (4, 5) }]
+        );
+        assert_eq!(
+            explanation.to_string(),
+            "treb7uchet = 77\n      4..5   7 = 7 *\n    warning: the token at 4..5 is both first and last\n"
+        );
+
+        let explanation = CalibrationVocabulary::digits().explain("abc");
+        assert_eq!(explanation.value(), None);
+        assert_eq!(explanation.warnings(), [Warning::NoTokens]);
+    }
+
+    #[test]
+    fn builder_error_test() {
+        let builder = CalibrationVocabulary::builder();
+        assert_eq!(
+            builder.clone().build().unwrap_err(),
+            VocabularyError::NoTokens
+        );
+        assert_eq!(
+            builder.clone().token("", 1).build().unwrap_err(),
+            VocabularyError::EmptyToken
+        );
+        assert_eq!(
+            builder.digits().base(1).build().unwrap_err(),
+            VocabularyError::Base(1)
+        );
+    }
 }
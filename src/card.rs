@@ -1,6 +1,8 @@
+use crate::{error::ParseError, parse::InputLine};
 use std::{
     cmp::Ordering,
     collections::{HashMap, HashSet},
+    fmt::Display,
     str::FromStr,
 };
 
@@ -83,12 +85,13 @@ impl Cards {
         card_counts: &HashMap<Card, u32>,
     ) -> Option<HandType> {
         if let Some((three, _)) = card_counts.iter().find(|(_, &v)| v == 3) {
-            let other: Vec<_> = card_counts
+            let mut other: Vec<_> = card_counts
                 .iter()
                 .filter(|(k, &v)| v != 3)
                 .map(|(k, _)| *k)
                 .collect();
             debug_assert_eq!(other.len(), 2);
+            other.sort();
             return Some(HandType::ThreeOfAKind {
                 card: *three,
                 other: (other[0], other[1]),
@@ -119,12 +122,13 @@ impl Cards {
 
     fn get_one_pair(cards: &[Card; 5], card_counts: &HashMap<Card, u32>) -> Option<HandType> {
         if let Some((card, _)) = card_counts.iter().find(|(_, c)| **c == 2) {
-            let other: Vec<_> = card_counts
+            let mut other: Vec<_> = card_counts
                 .iter()
                 .filter(|(_, c)| **c == 1)
                 .map(|(card, _)| card)
                 .collect();
             debug_assert_eq!(other.len(), 3);
+            other.sort();
             Some(HandType::OnePair {
                 pair: *card,
                 other: (*other[0], *other[1], *other[2]),
@@ -163,6 +167,36 @@ impl Cards {
     }
 }
 
+impl FromStr for Cards {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 32T3K 765
+        let line = InputLine::new(1, s);
+        let (hand, bid) = line.split_once(s, " ")?;
+        let bid = line.parse(bid, "a bid")?;
+
+        let mut cards = Vec::with_capacity(5);
+        for (i, c) in hand.char_indices() {
+            cards.push(Card::try_from(c).map_err(|e| line.locate(&hand[i..], e))?);
+        }
+        let cards: [Card; 5] = cards
+            .try_into()
+            .map_err(|_| line.error(hand, "a hand of five cards"))?;
+
+        Ok(Self::new(cards, bid))
+    }
+}
+
+impl Display for Cards {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for card in self.cards {
+            write!(f, "{card}")?;
+        }
+        write!(f, " {}", self.bid)
+    }
+}
+
 impl Ord for Cards {
     fn cmp(&self, other: &Self) -> Ordering {
         let ord = self
@@ -194,9 +228,12 @@ impl PartialOrd for Cards {
 pub struct Card(u32);
 
 impl Card {
-    pub fn new(v: u32) -> Result<Self, ()> {
+    pub fn new(v: u32) -> Result<Self, ParseError> {
         if !(1..=14).contains(&v) {
-            Err(())
+            Err(ParseError::new(
+                v.to_string(),
+                "a card value between 1 and 14",
+            ))
         } else {
             Ok(Self(v))
         }
@@ -220,24 +257,35 @@ impl PartialOrd for Card {
 }
 
 impl TryFrom<char> for Card {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
-        if let Some(v) = value.to_digit(10) {
-            Ok(Self(v))
-        } else {
-            match value {
-                'A' => Ok(Self(14)),
-                'K' => Ok(Self(13)),
-                'Q' => Ok(Self(12)),
-                'J' => Ok(Self(11)),
-                'T' => Ok(Self(10)),
-                _ => Err(()),
-            }
+        match value {
+            '2'..='9' => Ok(Self(value.to_digit(10).unwrap())),
+            'A' => Ok(Self(14)),
+            'K' => Ok(Self(13)),
+            'Q' => Ok(Self(12)),
+            'J' => Ok(Self(11)),
+            'T' => Ok(Self(10)),
+            _ => Err(ParseError::new(value, "a card (2-9, T, J, Q, K or A)")),
         }
     }
 }
 
+impl Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self.0 {
+            14 => 'A',
+            13 => 'K',
+            12 => 'Q',
+            11 => 'J',
+            10 => 'T',
+            v => char::from_digit(v, 10).unwrap(),
+        };
+        write!(f, "{c}")
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum HandType {
     FiveOfAKind(Card),
@@ -285,3 +333,23 @@ impl HandType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_test() {
+        for line in [
+            "32T3K 765",
+            "T55J5 684",
+            "KK677 28",
+            "KTJJT 220",
+            "QQQJA 483",
+        ] {
+            let cards: Cards = line.parse().unwrap();
+            assert_eq!(cards.to_string(), line);
+            assert_eq!(cards.to_string().parse::<Cards>(), Ok(cards));
+        }
+    }
+}
@@ -1,10 +1,342 @@
-#[derive(Default, Clone, Copy)]
+use crate::{error::ParseError, parse::InputLine};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    ops::{Add, AddAssign},
+    str::FromStr,
+};
+
+/// Number of cubes per colour, colours that aren't in the set count as zero.
+///
+/// Zero counts are never stored, so sets that only differ in them are equal.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub struct CubeSet {
-    pub red: u32,
-    pub green: u32,
-    pub blue: u32,
+    counts: BTreeMap<String, u32>,
+}
+
+impl CubeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder style [`CubeSet::set`].
+    pub fn with(mut self, colour: impl Into<String>, count: u32) -> Self {
+        self.set(colour, count);
+        self
+    }
+
+    pub fn get(&self, colour: &str) -> u32 {
+        self.counts.get(colour).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, colour: impl Into<String>, count: u32) {
+        let colour = colour.into();
+        if count == 0 {
+            self.counts.remove(&colour);
+        } else {
+            self.counts.insert(colour, count);
+        }
+    }
+
+    /// Colours with at least one cube, in alphabetical order.
+    pub fn colours(&self) -> impl Iterator<Item = &str> {
+        self.counts.keys().map(|c| c.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.counts.iter().map(|(c, n)| (c.as_str(), *n))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Number of cubes of every colour, saturating at `u32::MAX`.
+    pub fn total(&self) -> u32 {
+        self.counts
+            .values()
+            .fold(0, |total: u32, &n| total.saturating_add(n))
+    }
+
+    /// Element-wise sum, `None` if any count or the total overflows.
+    pub fn checked_add(&self, other: &CubeSet) -> Option<CubeSet> {
+        self.total().checked_add(other.total())?;
+        let mut sum = self.clone();
+        for (colour, count) in other.iter() {
+            sum.set(colour, sum.get(colour).checked_add(count)?);
+        }
+        Some(sum)
+    }
+
+    /// Element-wise maximum, the smallest set containing both.
+    pub fn union(&self, other: &CubeSet) -> CubeSet {
+        let mut union = self.clone();
+        for (colour, count) in other.iter() {
+            if count > union.get(colour) {
+                union.set(colour, count);
+            }
+        }
+        union
+    }
+
+    /// Element-wise minimum.
+    pub fn intersection(&self, other: &CubeSet) -> CubeSet {
+        self.iter()
+            .map(|(colour, count)| (colour, count.min(other.get(colour))))
+            .collect()
+    }
+
+    /// True if every colour has at most as many cubes as in `other`.
+    pub fn is_subset_of(&self, other: &CubeSet) -> bool {
+        self.iter()
+            .all(|(colour, count)| count <= other.get(colour))
+    }
+
+    /// Product of the counts of the colours in the set, zero for an empty set.
+    pub fn power(&self) -> u64 {
+        if self.is_empty() {
+            return 0;
+        }
+        self.counts.values().map(|&n| u64::from(n)).product()
+    }
+
+    /// Product of the counts of every colour in `palette`, so a missing colour makes it zero.
+    pub fn power_over<'a>(&self, palette: impl IntoIterator<Item = &'a str>) -> u64 {
+        palette
+            .into_iter()
+            .map(|colour| u64::from(self.get(colour)))
+            .product()
+    }
+
+    /// Parses "3 blue, 4 red", only accepting colours in `palette` if there is one. The
+    /// total must fit in a `u32`.
+    pub(crate) fn parse(
+        line: InputLine<'_>,
+        grab: &str,
+        palette: Option<&[&str]>,
+    ) -> Result<Self, ParseError> {
+        let mut cube_set = CubeSet::new();
+        let mut total: u32 = 0;
+        for part in grab.split(", ") {
+            // 3 blue
+            let (num_text, colour) = line.split_once(part, " ")?;
+            let num = line.parse(num_text, "a number of cubes")?;
+            total = total
+                .checked_add(num)
+                .ok_or_else(|| line.error(num_text, "a grab of at most 4294967295 cubes"))?;
+            let known = match palette {
+                Some(palette) => palette.contains(&colour),
+                None => !colour.is_empty() && colour.chars().all(char::is_alphabetic),
+            };
+            if !known {
+                return Err(line.error(colour, &expected_colour(palette)));
+            }
+            if cube_set.counts.contains_key(colour) {
+                return Err(line.error(colour, "a colour that wasn't mentioned yet"));
+            }
+            cube_set.set(colour, num);
+        }
+
+        Ok(cube_set)
+    }
+}
+
+/// "'red', 'green' or 'blue'"
+fn expected_colour(palette: Option<&[&str]>) -> String {
+    let Some((last, rest)) = palette.and_then(|p| p.split_last()) else {
+        return "a colour".to_string();
+    };
+    let quoted: Vec<_> = rest.iter().map(|c| format!("'{c}'")).collect();
+    match quoted.is_empty() {
+        true => format!("'{last}'"),
+        false => format!("{} or '{last}'", quoted.join(", ")),
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, u32)> for CubeSet {
+    fn from_iter<T: IntoIterator<Item = (S, u32)>>(iter: T) -> Self {
+        iter.into_iter()
+            .fold(CubeSet::new(), |set, (colour, count)| {
+                set.with(colour, count)
+            })
+    }
+}
+
+/// Element-wise sum, saturating at `u32::MAX`; see [`CubeSet::checked_add`].
+impl Add for &CubeSet {
+    type Output = CubeSet;
+
+    fn add(self, rhs: &CubeSet) -> CubeSet {
+        let mut sum = self.clone();
+        sum += rhs;
+        sum
+    }
+}
+
+impl Add for CubeSet {
+    type Output = CubeSet;
+
+    fn add(mut self, rhs: CubeSet) -> CubeSet {
+        self += &rhs;
+        self
+    }
 }
 
-pub fn is_possible(limit: CubeSet, grab: CubeSet) -> bool {
-    !(limit.red < grab.red || limit.green < grab.green || limit.blue < grab.blue)
+impl AddAssign<&CubeSet> for CubeSet {
+    fn add_assign(&mut self, rhs: &CubeSet) {
+        for (colour, count) in rhs.iter() {
+            let sum = self.get(colour).saturating_add(count);
+            self.set(colour, sum);
+        }
+    }
+}
+
+impl FromStr for CubeSet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(InputLine::new(1, s), s, None)
+    }
+}
+
+impl Display for CubeSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<_> = self
+            .iter()
+            .map(|(colour, count)| format!("{count} {colour}"))
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    id: u32,
+    grabs: Vec<CubeSet>,
+}
+
+impl Game {
+    pub fn new(id: u32, grabs: Vec<CubeSet>) -> Self {
+        Self { id, grabs }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn grabs(&self) -> &[CubeSet] {
+        &self.grabs
+    }
+
+    /// The smallest bag every grab could have come from.
+    pub fn minimum_bag(&self) -> CubeSet {
+        self.grabs
+            .iter()
+            .fold(CubeSet::new(), |bag, grab| bag.union(grab))
+    }
+
+    pub fn is_possible_with(&self, bag: &CubeSet) -> bool {
+        self.grabs.iter().all(|grab| grab.is_subset_of(bag))
+    }
+
+    /// Parses "Game 1: 3 blue, 4 red; 1 red", see [`CubeSet::parse`] for `palette`. The
+    /// grabs together must fit in a `u32`, so they can be summed if they weren't put back.
+    pub(crate) fn parse(line: InputLine<'_>, palette: Option<&[&str]>) -> Result<Self, ParseError> {
+        let s = line.text();
+        let (game_id, game_grabs) = line.split_once(s, ": ")?;
+        let id = line.strip_prefix(game_id, "Game ")?;
+        let id = line.parse(id, "a game id")?;
+        let mut sum = CubeSet::new();
+        let mut grabs = Vec::new();
+        for grab in game_grabs.split("; ") {
+            let cube_set = CubeSet::parse(line, grab, palette)?;
+            sum = sum
+                .checked_add(&cube_set)
+                .ok_or_else(|| line.error(grab, "grabs of at most 4294967295 cubes in total"))?;
+            grabs.push(cube_set);
+        }
+
+        Ok(Self::new(id, grabs))
+    }
+}
+
+impl FromStr for Game {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(InputLine::new(1, s), None)
+    }
+}
+
+/// Grabs keep their order, colours within a grab are written alphabetically.
+impl Display for Game {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let grabs: Vec<_> = self.grabs.iter().map(|g| g.to_string()).collect();
+        write!(f, "Game {}: {}", self.id, grabs.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_round_trip_test() {
+        let line = "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red";
+        let game: Game = line.parse().unwrap();
+        assert_eq!(game.id(), 3);
+        assert_eq!(
+            game.grabs()[0],
+            CubeSet::new()
+                .with("red", 20)
+                .with("green", 8)
+                .with("blue", 6)
+        );
+        assert_eq!(game.to_string().parse::<Game>(), Ok(game));
+    }
+
+    #[test]
+    fn cube_set_test() {
+        let a: CubeSet = "3 blue, 4 red, 1 purple".parse().unwrap();
+        let b: CubeSet = "1 red, 2 green, 6 blue".parse().unwrap();
+        assert_eq!(a.union(&b).to_string(), "6 blue, 2 green, 1 purple, 4 red");
+        assert_eq!(a.intersection(&b).to_string(), "3 blue, 1 red");
+        assert_eq!((&a + &b).to_string(), "9 blue, 2 green, 1 purple, 5 red");
+        assert!(a.intersection(&b).is_subset_of(&a));
+        assert!(!a.is_subset_of(&b));
+        assert_eq!(a.power(), 12);
+        assert_eq!(a.power_over(["red", "green", "blue"]), 0);
+        assert_eq!(CubeSet::new().power(), 0);
+        assert_eq!(
+            a.clone().with("purple", 0),
+            "4 red, 3 blue".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let error = "1 red, 2 red".parse::<CubeSet>().unwrap_err();
+        assert_eq!((error.column(), error.text()), (Some(10), "red"));
+
+        let line = InputLine::new(1, "2 purple");
+        let error = CubeSet::parse(line, line.text(), Some(&["red", "blue"])).unwrap_err();
+        assert_eq!(error.expected(), "'red' or 'blue'");
+        assert!("2 3".parse::<CubeSet>().is_err());
+
+        let error = "4294967295 red, 1 blue".parse::<CubeSet>().unwrap_err();
+        assert_eq!((error.column(), error.text()), (Some(17), "1"));
+        let error = "Game 1: 4294967295 red; 1 red".parse::<Game>().unwrap_err();
+        assert_eq!((error.column(), error.text()), (Some(25), "1 red"));
+        assert!("Game 1: 4294967295 red; 0 red".parse::<Game>().is_ok());
+    }
+
+    #[test]
+    fn overflow_test() {
+        let max = CubeSet::new().with("red", u32::MAX);
+        let one = CubeSet::new().with("blue", 1);
+        assert_eq!(max.checked_add(&one), None);
+        assert_eq!(max.checked_add(&CubeSet::new()), Some(max.clone()));
+        assert_eq!((&max + &max).get("red"), u32::MAX);
+        assert_eq!((&max + &one).total(), u32::MAX);
+    }
 }
@@ -1,155 +1,922 @@
+use crate::{
+    error::ParseError,
+    parse::{parse_grid, write_grid},
+};
 use simple_grid::{Grid, GridIndex};
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
 
 type SchematicGrid = Grid<SchematicSymbol>;
 
-#[derive(Debug)]
+/// Cells that count as adjacent to a cell.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// Up, down, left and right.
+    Four,
+    /// Diagonals too.
+    #[default]
+    Eight,
+    /// `(column, row)` offsets.
+    Custom(Vec<(isize, isize)>),
+}
+
+impl Neighbourhood {
+    const FOUR: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+    const EIGHT: [(isize, isize); 8] = [
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+    ];
+
+    pub fn offsets(&self) -> &[(isize, isize)] {
+        match self {
+            Neighbourhood::Four => &Self::FOUR,
+            Neighbourhood::Eight => &Self::EIGHT,
+            Neighbourhood::Custom(offsets) => offsets,
+        }
+    }
+
+    /// Neighbours of `idx`, including ones past the right or bottom edge of the grid.
+    pub fn neighbours(&self, idx: GridIndex) -> impl Iterator<Item = GridIndex> + '_ {
+        self.offsets().iter().filter_map(move |&(column, row)| {
+            Some(GridIndex::new(
+                idx.column().checked_add_signed(column)?,
+                idx.row().checked_add_signed(row)?,
+            ))
+        })
+    }
+
+    /// Cells that have `idx` as a neighbour.
+    fn seen_from(&self, idx: GridIndex) -> impl Iterator<Item = GridIndex> + '_ {
+        self.offsets().iter().filter_map(move |&(column, row)| {
+            Some(GridIndex::new(
+                idx.column().checked_add_signed(column.checked_neg()?)?,
+                idx.row().checked_add_signed(row.checked_neg()?)?,
+            ))
+        })
+    }
+}
+
+/// How many parts a gear symbol must be adjacent to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    pub fn accepts(self, parts: usize) -> bool {
+        match self {
+            Arity::Exactly(n) => parts == n,
+            Arity::AtLeast(n) => parts >= n,
+        }
+    }
+}
+
+impl Default for Arity {
+    fn default() -> Self {
+        Arity::Exactly(2)
+    }
+}
+
+/// How the part numbers around a gear make its ratio.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Combine {
+    #[default]
+    Product,
+    Sum,
+}
+
+impl Combine {
+    /// Works in a u128, so the product of two numbers always fits. `None` if the result
+    /// doesn't.
+    pub fn apply(self, values: impl IntoIterator<Item = u64>) -> Option<u128> {
+        values
+            .into_iter()
+            .try_fold(self.identity(), |acc, value| self.step(acc, value))
+    }
+
+    fn identity(self) -> u128 {
+        match self {
+            Combine::Product => 1,
+            Combine::Sum => 0,
+        }
+    }
+
+    fn step(self, acc: u128, value: u64) -> Option<u128> {
+        match self {
+            Combine::Product => acc.checked_mul(value.into()),
+            Combine::Sum => acc.checked_add(value.into()),
+        }
+    }
+}
+
+/// What counts as a part and a gear, the defaults are the puzzle's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchematicRules {
+    neighbourhood: Neighbourhood,
+    part_symbols: Option<Vec<char>>,
+    gear_symbols: Vec<char>,
+    gear_arity: Arity,
+    combine: Combine,
+}
+
+impl SchematicRules {
+    pub fn new() -> Self {
+        Self {
+            neighbourhood: Neighbourhood::Eight,
+            part_symbols: None,
+            gear_symbols: vec!['*'],
+            gear_arity: Arity::Exactly(2),
+            combine: Combine::Product,
+        }
+    }
+
+    pub fn with_neighbourhood(mut self, neighbourhood: Neighbourhood) -> Self {
+        self.neighbourhood = neighbourhood;
+        self
+    }
+
+    /// Only these symbols mark numbers next to them as parts, by default any symbol does.
+    pub fn with_part_symbols(mut self, symbols: impl IntoIterator<Item = char>) -> Self {
+        self.part_symbols = Some(symbols.into_iter().collect());
+        self
+    }
+
+    pub fn with_gear_symbols(mut self, symbols: impl IntoIterator<Item = char>) -> Self {
+        self.gear_symbols = symbols.into_iter().collect();
+        self
+    }
+
+    pub fn with_gear_arity(mut self, arity: Arity) -> Self {
+        self.gear_arity = arity;
+        self
+    }
+
+    pub fn with_combine(mut self, combine: Combine) -> Self {
+        self.combine = combine;
+        self
+    }
+
+    pub fn neighbourhood(&self) -> &Neighbourhood {
+        &self.neighbourhood
+    }
+
+    pub fn gear_arity(&self) -> Arity {
+        self.gear_arity
+    }
+
+    pub fn combine(&self) -> Combine {
+        self.combine
+    }
+
+    pub fn is_part_symbol(&self, symbol: char) -> bool {
+        self.part_symbols
+            .as_ref()
+            .is_none_or(|symbols| symbols.contains(&symbol))
+    }
+
+    pub fn is_gear_symbol(&self, symbol: char) -> bool {
+        self.gear_symbols.contains(&symbol)
+    }
+}
+
+impl Default for SchematicRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
 pub struct Schematic {
     inner: SchematicGrid,
     parts: Vec<Part>,
-    index_to_part: HashMap<GridIndex, usize>,
+    /// Index in `parts` of the number covering each cell, row by row like the grid.
+    index_to_part: Vec<Option<u32>>,
 }
 
 impl Schematic {
-    pub fn new(inner: SchematicGrid) -> Self {
-        let (parts, index_to_part) = Self::fill_parts(&inner);
-        Self {
+    /// Fails if a number doesn't fit in a u64.
+    pub fn new(inner: SchematicGrid) -> Result<Self, ParseError> {
+        let mut index_to_part = vec![None; inner.width() * inner.height()];
+        let mut parts = Vec::new();
+        for row in 0..inner.height() {
+            for part in Self::scan_numbers(&inner, row, 0, inner.width())? {
+                Self::index_part(&mut index_to_part, inner.width(), &part, parts.len());
+                parts.push(part);
+            }
+        }
+
+        Ok(Self {
             inner,
             parts,
             index_to_part,
-        }
+        })
     }
 
+    /// In reading order, until the schematic is edited with [`Schematic::set`].
     pub fn parts(&self) -> &Vec<Part> {
         &self.parts
     }
 
-    pub fn part_numbers(&self) -> Vec<u32> {
-        let mut set = HashSet::new();
-        for (idx, part) in &self.index_to_part {
-            for neighbor in idx.neighbors() {
-                if let Some(SchematicSymbol::Symbol(_)) = self.inner.get(neighbor) {
-                    set.insert(self.parts[*part]);
-                }
-            }
+    /// Index in [`Schematic::parts`] of the number with a digit at `idx`.
+    pub fn part_at(&self, idx: GridIndex) -> Option<usize> {
+        if idx.column() >= self.inner.width() || idx.row() >= self.inner.height() {
+            return None;
         }
-        set.iter().map(|v| v.value).collect()
+        self.index_to_part[idx.row() * self.inner.width() + idx.column()].map(|p| p as usize)
     }
 
-    pub fn gear_ratios(&self) -> Vec<u32> {
-        let mut gears = Vec::new();
-        for idx in self.inner.indices() {
-            if let Some(SchematicSymbol::Symbol('*')) = self.inner.get(idx) {
-                let mut adjacent_parts = HashSet::new();
-                for neighbor in idx.neighbors() {
-                    if let Some(part) = self.index_to_part.get(&neighbor) {
-                        adjacent_parts.insert(self.parts[*part]);
-                    }
-                }
+    pub fn part_numbers(&self) -> Vec<u64> {
+        self.part_numbers_with(&SchematicRules::default())
+    }
 
-                if adjacent_parts.len() == 2 {
-                    gears.push(adjacent_parts.iter().map(|p| p.value).product());
-                }
+    pub fn part_numbers_with(&self, rules: &SchematicRules) -> Vec<u64> {
+        self.parts
+            .iter()
+            .filter(|part| self.is_part_number(part, rules))
+            .map(|part| part.value)
+            .collect()
+    }
+
+    fn is_part_number(&self, part: &Part, rules: &SchematicRules) -> bool {
+        part.indices().any(|idx| {
+            rules.neighbourhood().neighbours(idx).any(|neighbor| {
+                matches!(self.inner.get(neighbor), Some(SchematicSymbol::Symbol(c)) if rules.is_part_symbol(*c))
+            })
+        })
+    }
+
+    /// Adjacency of parts and symbols, using the 8-way neighbourhood.
+    pub fn graph(&self) -> SchematicGraph {
+        self.graph_with(&SchematicRules::default())
+    }
+
+    /// Every symbol is a node, whether or not `rules` count it as a part or gear symbol,
+    /// only the neighbourhood is used.
+    pub fn graph_with(&self, rules: &SchematicRules) -> SchematicGraph {
+        let mut symbols = Vec::new();
+        let mut symbol_parts = Vec::new();
+        let mut part_symbols = vec![Vec::new(); self.parts.len()];
+        for idx in self.inner.indices() {
+            let SchematicSymbol::Symbol(symbol) = self.inner[idx] else {
+                continue;
+            };
+            let mut parts: Vec<usize> = rules
+                .neighbourhood()
+                .neighbours(idx)
+                .filter_map(|neighbor| self.part_at(neighbor))
+                .collect();
+            parts.sort_unstable();
+            parts.dedup();
+            for &part in &parts {
+                part_symbols[part].push(symbols.len());
             }
+            symbols.push(Symbol {
+                position: idx,
+                symbol,
+            });
+            symbol_parts.push(parts);
+        }
+
+        SchematicGraph {
+            parts: self.parts.clone(),
+            symbols,
+            part_symbols,
+            symbol_parts,
         }
-        gears
     }
 
-    fn fill_parts(grid: &SchematicGrid) -> (Vec<Part>, HashMap<GridIndex, usize>) {
-        let mut parts = Vec::new();
-        let mut indices_to_parts = HashMap::new();
-        for end_idx in grid.indices() {
-            // check if a part ends at this index
-            if let SchematicSymbol::Number(num) = grid[end_idx] {
-                if Self::is_end_of_number(grid, end_idx) {
-                    let mut value = 0;
-                    for l in 0.. {
-                        let part_index = GridIndex::new(end_idx.column() - l, end_idx.row());
-                        indices_to_parts.insert(part_index, parts.len()); // this is where the part will go when finished parsing
-                        value += grid[part_index].unwrap_number() * 10_u32.pow(l as u32);
-                        if Self::is_start_of_number(grid, part_index) {
-                            parts.push(Part::new(part_index, value, l + 1));
-                            break;
-                        }
-                    }
-                }
+    pub fn gear_ratios(&self) -> Vec<u128> {
+        self.gear_ratios_with(&SchematicRules::default())
+    }
+
+    /// Gears whose ratio doesn't fit in a u128 are left out, which takes more than two
+    /// numbers around a gear.
+    pub fn gear_ratios_with(&self, rules: &SchematicRules) -> Vec<u128> {
+        self.inner
+            .indices()
+            .filter_map(|idx| self.gear_ratio(idx, rules))
+            .collect()
+    }
+
+    /// Ratio of the gear at `idx`, if there is one and its ratio fits in a u128.
+    fn gear_ratio(&self, idx: GridIndex, rules: &SchematicRules) -> Option<u128> {
+        let Some(SchematicSymbol::Symbol(c)) = self.inner.get(idx) else {
+            return None;
+        };
+        if !rules.is_gear_symbol(*c) {
+            return None;
+        }
+        let combine = rules.combine();
+        let mut count = 0;
+        let mut ratio = combine.identity();
+        let neighbourhood = rules.neighbourhood();
+        for (i, neighbor) in neighbourhood.neighbours(idx).enumerate() {
+            let Some(part) = self.part_at(neighbor) else {
+                continue;
+            };
+            // a number seen through several of its digits only counts once
+            let seen = neighbourhood
+                .neighbours(idx)
+                .take(i)
+                .any(|earlier| self.part_at(earlier) == Some(part));
+            if !seen {
+                count += 1;
+                ratio = combine.step(ratio, self.parts[part].value)?;
             }
         }
 
-        (parts, indices_to_parts)
+        rules.gear_arity().accepts(count).then_some(ratio)
     }
 
-    fn is_part_adjacent_to_symbol(grid: &SchematicGrid, part: &Part) -> bool {
-        for idx in part.indices() {
-            for neighbor in idx.neighbors() {
-                if Self::is_symbol(grid, neighbor) {
-                    return true;
-                }
+    /// Replaces the symbol at `idx`, joining, splitting or changing the numbers next to it.
+    ///
+    /// Only the numbers on either side of `idx` are looked at again. The returned [`Edit`]
+    /// can bring a [`SchematicCache`] up to date. Fails without changing anything if the
+    /// edit makes a number that doesn't fit in a u64, panics if `idx` is outside the grid.
+    pub fn set(&mut self, idx: GridIndex, symbol: SchematicSymbol) -> Result<Edit, ParseError> {
+        let previous = self.inner.replace_cell(idx, symbol);
+
+        let mut affected: Vec<usize> = [Some(idx), idx.left(), idx.right()]
+            .into_iter()
+            .flatten()
+            .filter_map(|i| self.part_at(i))
+            .collect();
+        affected.sort_unstable();
+        affected.dedup();
+        let mut from = idx.column();
+        for &part in &affected {
+            from = from.min(self.parts[part].start.column());
+        }
+        let to = affected
+            .iter()
+            .map(|&part| self.parts[part].start.column() + self.parts[part].len)
+            .fold(idx.column() + 1, usize::max);
+        let added = match Self::scan_numbers(&self.inner, idx.row(), from, to) {
+            Ok(added) => added,
+            Err(e) => {
+                self.inner.replace_cell(idx, previous);
+                return Err(e);
             }
+        };
+
+        // backwards, so the parts that still have to be removed don't move
+        let mut removed = Vec::new();
+        for &part in affected.iter().rev() {
+            removed.push(self.remove_part(part));
+        }
+        removed.sort_by_key(|part| part.start.column());
+        for part in &added {
+            Self::index_part(
+                &mut self.index_to_part,
+                self.inner.width(),
+                part,
+                self.parts.len(),
+            );
+            self.parts.push(*part);
         }
 
-        false
+        Ok(Edit {
+            position: idx,
+            previous,
+            removed,
+            added,
+        })
     }
 
-    fn is_symbol(grid: &SchematicGrid, idx: GridIndex) -> bool {
-        matches!(grid.get(idx), Some(SchematicSymbol::Symbol(_)))
+    /// Swap removes a part, so the last part takes its index.
+    fn remove_part(&mut self, part: usize) -> Part {
+        let width = self.inner.width();
+        let removed = self.parts.swap_remove(part);
+        for idx in removed.indices() {
+            self.index_to_part[idx.row() * width + idx.column()] = None;
+        }
+        if let Some(moved) = self.parts.get(part) {
+            Self::index_part(&mut self.index_to_part, width, moved, part);
+        }
+        removed
     }
 
-    fn is_start_of_number(grid: &SchematicGrid, idx: GridIndex) -> bool {
-        if let Some(left) = idx.left() {
-            !Self::is_number(grid, left)
-        } else {
-            true
-        }
+    fn index_part(index_to_part: &mut [Option<u32>], width: usize, part: &Part, index: usize) {
+        let index = u32::try_from(index).expect("fewer than 2^32 parts");
+        let start = part.start.row() * width + part.start.column();
+        index_to_part[start..start + part.len].fill(Some(index));
     }
 
-    fn is_end_of_number(grid: &SchematicGrid, idx: GridIndex) -> bool {
-        if let Some(right) = idx.right() {
-            !Self::is_number(grid, right)
-        } else {
-            true
+    /// Numbers in `row` that start in `from..to`, including the end of one that runs past `to`.
+    fn scan_numbers(
+        grid: &SchematicGrid,
+        row: usize,
+        from: usize,
+        to: usize,
+    ) -> Result<Vec<Part>, ParseError> {
+        let mut parts = Vec::new();
+        let mut column = from;
+        while column < to {
+            let start = GridIndex::new(column, row);
+            let mut value: Option<u64> = Some(0);
+            while let Some(SchematicSymbol::Number(digit)) = grid.get(GridIndex::new(column, row)) {
+                value = value
+                    .and_then(|v| v.checked_mul(10))
+                    .and_then(|v| v.checked_add(u64::from(*digit)));
+                column += 1;
+            }
+            let len = column - start.column();
+            if len == 0 {
+                column += 1;
+                continue;
+            }
+            let Some(value) = value else {
+                let digits: String = (start.column()..column)
+                    .map(|c| grid[GridIndex::new(c, row)].to_string())
+                    .collect();
+                return Err(ParseError::new(digits, "a number that fits in 64 bits")
+                    .at(row + 1, start.column() + 1));
+            };
+            parts.push(Part::new(start, value, len));
         }
+
+        Ok(parts)
     }
+}
+
+impl FromStr for Schematic {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (w, h, symbols) = parse_grid(s, |c| Ok(SchematicSymbol::from(c)))?;
 
-    fn is_number(grid: &SchematicGrid, idx: GridIndex) -> bool {
-        matches!(grid.get(idx), Some(SchematicSymbol::Number(_)))
+        Self::new(Grid::new(w, h, symbols))
+    }
+}
+
+impl Display for Schematic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_grid(&self.inner, f)
     }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 pub struct Part {
     start: GridIndex,
-    value: u32,
+    value: u64,
     len: usize,
 }
 
 impl Part {
-    fn new(start: GridIndex, value: u32, len: usize) -> Self {
+    fn new(start: GridIndex, value: u64, len: usize) -> Self {
         Self { start, value, len }
     }
 
-    pub fn value(&self) -> u32 {
+    pub fn value(&self) -> u64 {
         self.value
     }
 
-    fn indices(&self) -> impl Iterator<Item = GridIndex> + '_ {
+    /// Position of the first digit.
+    pub fn start(&self) -> GridIndex {
+        self.start
+    }
+
+    /// Positions of the digits, left to right.
+    pub fn indices(&self) -> impl Iterator<Item = GridIndex> + '_ {
         (0..self.len).map(|c| GridIndex::new(self.start.column() + c, self.start.row()))
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// What changed in a call to [`Schematic::set`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    position: GridIndex,
+    previous: SchematicSymbol,
+    removed: Vec<Part>,
+    added: Vec<Part>,
+}
+
+impl Edit {
+    pub fn position(&self) -> GridIndex {
+        self.position
+    }
+
+    pub fn previous(&self) -> SchematicSymbol {
+        self.previous
+    }
+
+    /// Numbers that were next to or at the position before the edit.
+    pub fn removed(&self) -> &[Part] {
+        &self.removed
+    }
+
+    /// Numbers that are next to or at the position after the edit, unchanged ones included.
+    pub fn added(&self) -> &[Part] {
+        &self.added
+    }
+}
+
+/// Part numbers and gear ratios of a schematic, kept up to date through its edits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchematicCache {
+    rules: SchematicRules,
+    /// Value of every part number, by the position of its first digit.
+    part_numbers: HashMap<GridIndex, u64>,
+    /// Ratio of every gear, by its position.
+    gears: HashMap<GridIndex, u128>,
+}
+
+impl SchematicCache {
+    pub fn new(schematic: &Schematic, rules: SchematicRules) -> Self {
+        let part_numbers = schematic
+            .parts
+            .iter()
+            .filter(|part| schematic.is_part_number(part, &rules))
+            .map(|part| (part.start, part.value))
+            .collect();
+        let gears = schematic
+            .inner
+            .indices()
+            .filter_map(|idx| Some((idx, schematic.gear_ratio(idx, &rules)?)))
+            .collect();
+
+        Self {
+            rules,
+            part_numbers,
+            gears,
+        }
+    }
+
+    pub fn rules(&self) -> &SchematicRules {
+        &self.rules
+    }
+
+    /// Recomputes what `edit` could have changed, `schematic` must be the edited schematic.
+    ///
+    /// Only the numbers that see the edited cell are checked again, and the gears that
+    /// see the edited cell or one of the numbers next to it.
+    pub fn update(&mut self, schematic: &Schematic, edit: &Edit) {
+        let neighbourhood = self.rules.neighbourhood();
+        for part in &edit.removed {
+            self.part_numbers.remove(&part.start);
+        }
+        let seeing_edit: Vec<_> = neighbourhood
+            .seen_from(edit.position)
+            .filter_map(|idx| schematic.part_at(idx))
+            .map(|part| schematic.parts[part])
+            .collect();
+        for part in edit.added.iter().chain(&seeing_edit) {
+            if schematic.is_part_number(part, &self.rules) {
+                self.part_numbers.insert(part.start, part.value);
+            } else {
+                self.part_numbers.remove(&part.start);
+            }
+        }
+
+        let changed = edit
+            .removed
+            .iter()
+            .chain(&edit.added)
+            .flat_map(|part| part.indices())
+            .chain([edit.position]);
+        let mut gears: HashSet<_> = changed
+            .flat_map(|idx| neighbourhood.seen_from(idx))
+            .collect();
+        gears.insert(edit.position);
+        for idx in gears {
+            match schematic.gear_ratio(idx, &self.rules) {
+                Some(ratio) => self.gears.insert(idx, ratio),
+                None => self.gears.remove(&idx),
+            };
+        }
+    }
+
+    pub fn part_numbers(&self) -> impl Iterator<Item = u64> + '_ {
+        self.part_numbers.values().copied()
+    }
+
+    pub fn gear_ratios(&self) -> impl Iterator<Item = u128> + '_ {
+        self.gears.values().copied()
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub struct Symbol {
+    position: GridIndex,
+    symbol: char,
+}
+
+impl Symbol {
+    pub fn position(&self) -> GridIndex {
+        self.position
+    }
+
+    pub fn symbol(&self) -> char {
+        self.symbol
+    }
+}
+
+/// Bipartite graph of the parts and symbols of a schematic, with an edge between a part
+/// and every symbol next to one of its digits.
+///
+/// Nodes are referred to by their index in [`SchematicGraph::parts`] and
+/// [`SchematicGraph::symbols`], in the order of [`Schematic::parts`] and reading order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchematicGraph {
+    parts: Vec<Part>,
+    symbols: Vec<Symbol>,
+    part_symbols: Vec<Vec<usize>>,
+    symbol_parts: Vec<Vec<usize>>,
+}
+
+impl SchematicGraph {
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Index of the symbol at `position`, if there is one.
+    pub fn symbol_at(&self, position: GridIndex) -> Option<usize> {
+        self.symbols.iter().position(|s| s.position == position)
+    }
+
+    /// Every `(part, symbol)` edge, ordered by symbol.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.symbol_parts
+            .iter()
+            .enumerate()
+            .flat_map(|(symbol, parts)| parts.iter().map(move |&part| (part, symbol)))
+    }
+
+    pub fn parts_touching(&self, symbol: usize) -> &[usize] {
+        &self.symbol_parts[symbol]
+    }
+
+    pub fn symbols_touching(&self, part: usize) -> &[usize] {
+        &self.part_symbols[part]
+    }
+
+    /// Parts that aren't next to any symbol.
+    pub fn orphans(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.parts.len()).filter(|&part| self.part_symbols[part].is_empty())
+    }
+
+    /// Parts grouped by the symbols they share, directly or through other parts.
+    ///
+    /// Every part is in exactly one component, orphans in one of their own. Components are
+    /// sorted, and ordered by their first part.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.parts.len()];
+        let mut components = Vec::new();
+        for first in 0..self.parts.len() {
+            if seen[first] {
+                continue;
+            }
+            seen[first] = true;
+            let mut component = vec![first];
+            let mut stack = vec![first];
+            while let Some(part) = stack.pop() {
+                for &symbol in &self.part_symbols[part] {
+                    for &next in &self.symbol_parts[symbol] {
+                        if !seen[next] {
+                            seen[next] = true;
+                            component.push(next);
+                            stack.push(next);
+                        }
+                    }
+                }
+            }
+            component.sort_unstable();
+            components.push(component);
+        }
+
+        components
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SchematicSymbol {
     Number(u32),
     Symbol(char),
     Period,
 }
 
-impl SchematicSymbol {
-    fn unwrap_number(self) -> u32 {
-        if let Self::Number(v) = self {
-            v
-        } else {
-            panic!()
+impl From<char> for SchematicSymbol {
+    fn from(c: char) -> Self {
+        match c.to_digit(10) {
+            Some(digit) => SchematicSymbol::Number(digit),
+            None if c == '.' => SchematicSymbol::Period,
+            None => SchematicSymbol::Symbol(c),
         }
     }
 }
+
+impl Display for SchematicSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchematicSymbol::Number(digit) => write!(f, "{digit}"),
+            SchematicSymbol::Symbol(c) => write!(f, "{c}"),
+            SchematicSymbol::Period => write!(f, "."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::Lcg;
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn round_trip_test() {
+        let schematic: Schematic = EXAMPLE.parse().unwrap();
+        assert_eq!(schematic.to_string(), EXAMPLE);
+        assert_eq!(schematic.to_string().parse::<Schematic>(), Ok(schematic));
+    }
+
+    #[test]
+    fn rules_test() {
+        let schematic: Schematic = EXAMPLE.parse().unwrap();
+        let sum = |values: Vec<u64>| values.into_iter().sum::<u64>();
+        let sum_ratios = |values: Vec<u128>| values.into_iter().sum::<u128>();
+        let rules = SchematicRules::default();
+        assert_eq!(sum(schematic.part_numbers_with(&rules)), 4361);
+        assert_eq!(sum_ratios(schematic.gear_ratios_with(&rules)), 467835);
+
+        // 467, 755 and 592 only touch a symbol diagonally
+        let four = SchematicRules::new().with_neighbourhood(Neighbourhood::Four);
+        assert_eq!(
+            sum(schematic.part_numbers_with(&four)),
+            35 + 633 + 617 + 664 + 598
+        );
+
+        let dollar = SchematicRules::new().with_part_symbols(['$']);
+        assert_eq!(sum(schematic.part_numbers_with(&dollar)), 664);
+
+        let any_star = SchematicRules::new()
+            .with_gear_arity(Arity::AtLeast(1))
+            .with_combine(Combine::Sum);
+        assert_eq!(
+            sum_ratios(schematic.gear_ratios_with(&any_star)),
+            467 + 35 + 617 + 755 + 598
+        );
+
+        let custom = SchematicRules::new()
+            .with_neighbourhood(Neighbourhood::Custom(vec![(0, 1)]))
+            .with_gear_symbols(['#', '$'])
+            .with_gear_arity(Arity::Exactly(1));
+        assert_eq!(schematic.gear_ratios_with(&custom), [664]);
+    }
+
+    #[test]
+    fn graph_test() {
+        let schematic: Schematic = EXAMPLE.parse().unwrap();
+        let graph = schematic.graph();
+        let values = |parts: &[usize]| -> Vec<u64> {
+            parts.iter().map(|&p| graph.parts()[p].value()).collect()
+        };
+        assert_eq!(graph.symbols().len(), 6);
+        assert_eq!(graph.edges().count(), 8);
+
+        let star = graph.symbol_at(GridIndex::new(3, 1)).unwrap();
+        assert_eq!(values(graph.parts_touching(star)), [467, 35]);
+        let symbols: Vec<_> = graph
+            .symbols_touching(8)
+            .iter()
+            .map(|&s| graph.symbols()[s].symbol())
+            .collect();
+        assert_eq!((graph.parts()[8].value(), symbols), (664, vec!['$']));
+
+        let orphans: Vec<_> = graph.orphans().collect();
+        assert_eq!(values(&orphans), [114, 58]);
+        let components: Vec<_> = graph.components().iter().map(|c| values(c)).collect();
+        assert_eq!(
+            components,
+            [
+                vec![467, 35],
+                vec![114],
+                vec![633],
+                vec![617],
+                vec![58],
+                vec![592],
+                vec![755, 598],
+                vec![664],
+            ]
+        );
+    }
+
+    #[test]
+    fn set_test() {
+        let mut schematic: Schematic = EXAMPLE.parse().unwrap();
+        // join 467 and 114
+        schematic
+            .set(GridIndex::new(3, 0), SchematicSymbol::Number(0))
+            .unwrap();
+        let edit = schematic
+            .set(GridIndex::new(4, 0), SchematicSymbol::Number(9))
+            .unwrap();
+        let values = |parts: &[Part]| -> Vec<u64> { parts.iter().map(|p| p.value()).collect() };
+        assert_eq!(values(edit.removed()), [4670, 114]);
+        assert_eq!(values(edit.added()), [46_709_114]);
+        // split it again
+        let edit = schematic
+            .set(GridIndex::new(5, 0), SchematicSymbol::Symbol('*'))
+            .unwrap();
+        assert_eq!(values(edit.added()), [46709, 14]);
+        assert_eq!(edit.previous(), SchematicSymbol::Number(1));
+        assert!(schematic.to_string().starts_with("46709*14..\n"));
+    }
+
+    #[test]
+    fn cache_test() {
+        let mut schematic: Schematic = EXAMPLE.parse().unwrap();
+        let rules = [
+            SchematicRules::default(),
+            SchematicRules::new()
+                .with_neighbourhood(Neighbourhood::Custom(vec![(2, 0), (-1, 1)]))
+                .with_gear_arity(Arity::AtLeast(1)),
+        ];
+        let mut caches = rules.map(|rules| SchematicCache::new(&schematic, rules));
+        let symbols: Vec<_> = "0123456789.*#".chars().map(SchematicSymbol::from).collect();
+        fn sorted<T: Ord>(values: impl Iterator<Item = T>) -> Vec<T> {
+            let mut values: Vec<_> = values.collect();
+            values.sort_unstable();
+            values
+        }
+
+        let mut lcg = Lcg::new(12345);
+        let mut random = |n: usize| lcg.below(n as u64) as usize;
+        for _ in 0..500 {
+            let idx = GridIndex::new(random(10), random(10));
+            let edit = schematic.set(idx, symbols[random(symbols.len())]).unwrap();
+            let rebuilt: Schematic = schematic.to_string().parse().unwrap();
+            let mut parts = schematic.parts().clone();
+            parts.sort_by_key(|p| (p.start().row(), p.start().column()));
+            assert_eq!(&parts, rebuilt.parts());
+
+            for cache in &mut caches {
+                cache.update(&schematic, &edit);
+                assert_eq!(
+                    sorted(cache.part_numbers()),
+                    sorted(rebuilt.part_numbers_with(cache.rules()).into_iter())
+                );
+                assert_eq!(
+                    sorted(cache.gear_ratios()),
+                    sorted(rebuilt.gear_ratios_with(cache.rules()).into_iter())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn overflow_test() {
+        let max = u64::MAX.to_string();
+        let schematic: Schematic = format!("{max}*").parse().unwrap();
+        assert_eq!(schematic.part_numbers(), [u64::MAX]);
+
+        let error = format!("..{max}0").parse::<Schematic>().unwrap_err();
+        assert_eq!((error.line(), error.column()), (Some(1), Some(3)));
+
+        let mut schematic: Schematic = format!("{max}.1").parse().unwrap();
+        let error = schematic
+            .set(GridIndex::new(max.len(), 0), SchematicSymbol::Number(0))
+            .unwrap_err();
+        assert_eq!(error.text(), format!("{max}01"));
+        assert_eq!(schematic.to_string(), format!("{max}.1"));
+        assert_eq!(schematic.part_at(GridIndex::new(max.len() + 1, 0)), Some(1));
+
+        // ratios are products of two u64s
+        let schematic: Schematic = format!("{max}*{max}").parse().unwrap();
+        let ratio = u128::from(u64::MAX) * u128::from(u64::MAX);
+        assert_eq!(schematic.gear_ratios(), [ratio]);
+        let three = SchematicRules::new().with_gear_arity(Arity::Exactly(3));
+        let schematic: Schematic = format!("{max}*{max}\n{max}.{}", ".".repeat(max.len()))
+            .parse()
+            .unwrap();
+        assert_eq!(schematic.gear_ratios(), []);
+        assert_eq!(schematic.gear_ratios_with(&three), []);
+        let sum = SchematicRules::new()
+            .with_gear_arity(Arity::Exactly(3))
+            .with_combine(Combine::Sum);
+        assert_eq!(schematic.gear_ratios_with(&sum), [3 * u128::from(u64::MAX)]);
+        assert_eq!(Combine::Product.apply([u64::MAX; 3]), None);
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt::Display;
+
+/// Error returned by every parser in the crate.
+///
+/// Lines and columns are 1-based, columns count bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    day: Option<u32>,
+    line: Option<usize>,
+    column: Option<usize>,
+    text: String,
+    expected: String,
+}
+
+impl ParseError {
+    pub fn new(text: impl Into<String>, expected: impl Into<String>) -> Self {
+        Self {
+            day: None,
+            line: None,
+            column: None,
+            text: text.into(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Error for input that ends before line `line`.
+    pub fn missing_line(line: usize, expected: impl Into<String>) -> Self {
+        Self::new("", expected).at(line, 1)
+    }
+
+    pub fn with_day(mut self, day: u32) -> Self {
+        self.day = Some(day);
+        self
+    }
+
+    pub fn at(mut self, line: usize, column: usize) -> Self {
+        self.line = Some(line);
+        self.column = Some(column);
+        self
+    }
+
+    /// Moves the error down by `lines`, for errors found while parsing part of a larger input.
+    pub fn offset_lines(mut self, lines: usize) -> Self {
+        self.line = self.line.map(|l| l + lines);
+        self
+    }
+
+    pub fn day(&self) -> Option<u32> {
+        self.day
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {day}, ")?;
+        }
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, "line {line}, column {column}: ")?;
+        }
+        write!(f, "expected {}, found '{}'", self.expected, self.text)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_test() {
+        let error = ParseError::new("purple", "a colour");
+        assert_eq!(error.to_string(), "expected a colour, found 'purple'");
+        assert_eq!(
+            error.with_day(2).at(3, 11).to_string(),
+            "day 2, line 3, column 11: expected a colour, found 'purple'"
+        );
+    }
+
+    #[test]
+    fn offset_lines_test() {
+        let error = ParseError::new("x", "a number").at(1, 5).offset_lines(3);
+        assert_eq!((error.line(), error.column()), (Some(4), Some(5)));
+    }
+}
@@ -1,6 +1,11 @@
+use crate::{
+    error::ParseError,
+    parse::{parse_grid, write_grid},
+};
 use simple_grid::{Grid, GridIndex};
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GalaxyMap {
     map: Grid<GalaxyOrEmpty>,
     age: usize,
@@ -38,6 +43,11 @@ impl GalaxyMap {
         }
     }
 
+    /// Returns the same map where every empty row and column has grown by `age`.
+    pub fn with_age(&self, age: usize) -> Self {
+        Self::new(self.map.clone(), age)
+    }
+
     pub fn galaxies(&self) -> Vec<GridIndex> {
         self.map
             .indices()
@@ -56,6 +66,23 @@ impl GalaxyMap {
     }
 }
 
+/// Parses a map where every empty row and column is doubled, use [`GalaxyMap::with_age`] for older maps.
+impl FromStr for GalaxyMap {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (w, h, tiles) = parse_grid(s, GalaxyOrEmpty::try_from)?;
+
+        Ok(Self::new(Grid::new(w, h, tiles), 1))
+    }
+}
+
+impl Display for GalaxyMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_grid(&self.map, f)
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum GalaxyOrEmpty {
     Galaxy,
@@ -63,13 +90,13 @@ pub enum GalaxyOrEmpty {
 }
 
 impl TryFrom<char> for GalaxyOrEmpty {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
             '.' => Ok(Self::Empty),
             '#' => Ok(Self::Galaxy),
-            _ => Err(()),
+            _ => Err(ParseError::new(value, "'.' or '#'")),
         }
     }
 }
@@ -84,3 +111,26 @@ impl Display for GalaxyOrEmpty {
         write!(f, "{}", output)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_test() {
+        let input = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+        let map: GalaxyMap = input.parse().unwrap();
+        assert_eq!(map.to_string(), input);
+        assert_eq!(map.to_string().parse::<GalaxyMap>(), Ok(map.clone()));
+        assert_ne!(map.with_age(9), map);
+    }
+}
@@ -1,5 +1,7 @@
-use std::collections::HashSet;
+use crate::{error::ParseError, parse::InputLine};
+use std::{collections::HashSet, fmt::Display, str::FromStr};
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ValueHistory {
     values: Vec<i64>,
 }
@@ -22,7 +24,7 @@ impl ValueHistory {
             values = derived;
         }
 
-        final_values.into_iter().fold(0, |acc, curr| curr + acc)
+        final_values.into_iter().sum()
     }
 
     pub fn prev_value(&self) -> i64 {
@@ -45,6 +47,27 @@ impl ValueHistory {
     }
 }
 
+impl FromStr for ValueHistory {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = InputLine::new(1, s);
+        let values = s
+            .split(' ')
+            .map(|p| line.parse(p, "a number"))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(values))
+    }
+}
+
+impl Display for ValueHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let values: Vec<_> = self.values.iter().map(|v| v.to_string()).collect();
+        write!(f, "{}", values.join(" "))
+    }
+}
+
 fn derive(values: &[i64]) -> Vec<i64> {
     let mut derived = Vec::with_capacity(values.len() - 1);
     for w in values.windows(2) {
@@ -70,4 +93,11 @@ mod tests {
         let hist = ValueHistory::new(vec![10, 13, 16, 21, 30, 45]);
         assert_eq!(hist.prev_value(), 5);
     }
+
+    #[test]
+    fn round_trip_test() {
+        let hist: ValueHistory = "10 13 16 21 30 -45".parse().unwrap();
+        assert_eq!(hist, ValueHistory::new(vec![10, 13, 16, 21, 30, -45]));
+        assert_eq!(hist.to_string().parse::<ValueHistory>(), Ok(hist));
+    }
 }
@@ -0,0 +1,205 @@
+//! Guesses which day an input belongs to from the shape of its lines.
+
+use crate::solution;
+
+/// How well an input fits the grammar of one day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Match {
+    day: u32,
+    confidence: f64,
+}
+
+impl Match {
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    /// Between 0 and 1, 1 means every line fits and the day's parser accepts the input.
+    pub fn confidence(&self) -> f64 {
+        self.confidence
+    }
+}
+
+/// Grammar of a single day: a test for every line and one for the input as a whole.
+struct Fingerprint {
+    day: u32,
+    line: fn(&str) -> bool,
+    input: fn(&str) -> bool,
+}
+
+const FINGERPRINTS: [Fingerprint; 11] = [
+    Fingerprint {
+        day: 1,
+        line: |l| {
+            l.chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        },
+        input: |_| true,
+    },
+    Fingerprint {
+        day: 2,
+        line: |l| l.starts_with("Game ") && l.contains(": "),
+        input: |_| true,
+    },
+    Fingerprint {
+        day: 3,
+        line: |l| {
+            l.chars()
+                .all(|c| c.is_ascii_digit() || c.is_ascii_punctuation())
+        },
+        input: |s| s.contains(|c: char| c.is_ascii_digit()) && s.contains('.'),
+    },
+    Fingerprint {
+        day: 4,
+        line: |l| l.starts_with("Card ") && l.contains(':') && l.contains(" | "),
+        input: |_| true,
+    },
+    Fingerprint {
+        day: 5,
+        line: |l| l.starts_with("seeds: ") || l.ends_with(" map:") || is_numbers(l, Some(3)),
+        input: |s| s.trim_start().starts_with("seeds:"),
+    },
+    Fingerprint {
+        day: 6,
+        line: |l| {
+            let numbers = l.strip_prefix("Time:").or(l.strip_prefix("Distance:"));
+            numbers.is_some_and(|n| is_numbers(n, None))
+        },
+        input: |_| true,
+    },
+    Fingerprint {
+        day: 7,
+        line: |l| {
+            l.split_once(' ').is_some_and(|(hand, bid)| {
+                hand.len() == 5
+                    && hand.chars().all(|c| "23456789TJQKA".contains(c))
+                    && bid.parse::<u64>().is_ok()
+            })
+        },
+        input: |_| true,
+    },
+    Fingerprint {
+        day: 8,
+        line: |l| {
+            let is_id = |id: &str| id.len() == 3 && id.chars().all(|c| c.is_ascii_alphanumeric());
+            let is_node = l.is_ascii()
+                && l.len() == 16
+                && is_id(&l[0..3])
+                && &l[3..7] == " = ("
+                && is_id(&l[7..10])
+                && &l[10..12] == ", "
+                && is_id(&l[12..15])
+                && l.ends_with(')');
+            is_node || l.chars().all(|c| c == 'L' || c == 'R')
+        },
+        input: |s| {
+            s.lines()
+                .next()
+                .is_some_and(|l| !l.is_empty() && l.chars().all(|c| c == 'L' || c == 'R'))
+        },
+    },
+    Fingerprint {
+        day: 9,
+        line: |l| is_numbers(l, None),
+        input: |_| true,
+    },
+    Fingerprint {
+        day: 10,
+        line: |l| l.chars().all(|c| "|-LJ7F.S".contains(c)),
+        input: |s| s.matches('S').count() == 1,
+    },
+    Fingerprint {
+        day: 11,
+        line: |l| l.chars().all(|c| c == '.' || c == '#'),
+        input: |s| s.contains('#'),
+    },
+];
+
+/// Whitespace separated integers, optionally exactly `count` of them.
+fn is_numbers(s: &str, count: Option<usize>) -> bool {
+    let numbers: Vec<_> = s.split_whitespace().collect();
+    !numbers.is_empty()
+        && count.is_none_or(|c| numbers.len() == c)
+        && numbers.iter().all(|n| n.parse::<i64>().is_ok())
+}
+
+impl Fingerprint {
+    /// Fraction of non-empty lines that fit, halved if the day's parser rejects the input.
+    fn confidence(&self, input: &str) -> f64 {
+        let lines: Vec<_> = input.lines().filter(|l| !l.trim().is_empty()).collect();
+        if lines.is_empty() || !(self.input)(input) {
+            return 0.0;
+        }
+        let fitting = lines.iter().filter(|l| (self.line)(l)).count();
+        let score = fitting as f64 / lines.len() as f64;
+        let parses = solution::get(self.day).is_some_and(|s| s.parse(input).is_ok());
+
+        if parses {
+            score
+        } else {
+            score / 2.0
+        }
+    }
+}
+
+/// Scores `input` against every day, best match first. Days that don't fit at all are left out.
+pub fn identify(input: &str) -> Vec<Match> {
+    let mut matches: Vec<_> = FINGERPRINTS
+        .iter()
+        .map(|f| Match {
+            day: f.day,
+            confidence: f.confidence(input),
+        })
+        .filter(|m| m.confidence > 0.0)
+        .collect();
+    // stable, so ties go to the earlier day
+    matches.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    matches
+}
+
+/// The most likely day for `input`, if any day fits.
+pub fn best_match(input: &str) -> Option<Match> {
+    identify(input).into_iter().next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputStore;
+
+    #[test]
+    fn identify_examples_test() {
+        let store = InputStore::new("inputs");
+        let examples = [
+            (1, "example1.txt"),
+            (1, "example2.txt"),
+            (2, "example1.txt"),
+            (3, "example1.txt"),
+            (4, "example1.txt"),
+            (5, "example1.txt"),
+            (6, "example1.txt"),
+            (7, "example1.txt"),
+            (8, "example1.txt"),
+            (8, "example3.txt"),
+            (9, "example1.txt"),
+            (10, "example1.txt"),
+            (10, "example3.txt"),
+            (11, "example1.txt"),
+        ];
+        for (day, name) in examples {
+            let best = best_match(&store.read(day, name).unwrap()).unwrap();
+            assert_eq!(best.day(), day, "day {day}, {name}");
+            assert_eq!(best.confidence(), 1.0, "day {day}, {name}");
+        }
+    }
+
+    #[test]
+    fn identify_test() {
+        assert!(identify("").is_empty());
+        assert!(identify("hello world").is_empty());
+
+        let matches = identify("1 2 3\n4 5 6\nnot a number");
+        assert_eq!(matches[0].day(), 9);
+        assert!((matches[0].confidence() - 1.0 / 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    env,
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable pointing at a directory with (private) puzzle inputs.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Directory with the bundled example inputs, relative to the working directory.
+pub const DEFAULT_INPUT_DIR: &str = "inputs";
+
+pub const YEAR: u32 = 2023;
+
+/// Finds puzzle inputs on disk.
+///
+/// Every root is searched in order, first as `{root}/{year}/day{day}/{name}`
+/// and then as `{root}/day{day}/{name}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputStore {
+    roots: Vec<PathBuf>,
+    year: u32,
+}
+
+impl InputStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            roots: vec![root.into()],
+            year: YEAR,
+        }
+    }
+
+    /// Uses `AOC_INPUT_DIR` if it is set, falling back to the bundled `inputs` directory.
+    pub fn from_env() -> Self {
+        match env::var_os(INPUT_DIR_VAR) {
+            Some(dir) if !dir.is_empty() => Self::new(dir).with_root(DEFAULT_INPUT_DIR),
+            _ => Self::new(DEFAULT_INPUT_DIR),
+        }
+    }
+
+    /// Adds another root, searched after the existing ones.
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    pub fn with_year(mut self, year: u32) -> Self {
+        self.year = year;
+        self
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Every path that is tried for an input, in order.
+    pub fn candidates(&self, day: u32, name: &str) -> Vec<PathBuf> {
+        let day_dir = format!("day{day}");
+        self.roots
+            .iter()
+            .flat_map(|root| {
+                [
+                    root.join(self.year.to_string()).join(&day_dir).join(name),
+                    root.join(&day_dir).join(name),
+                ]
+            })
+            .collect()
+    }
+
+    /// Returns the first existing path for an input.
+    pub fn path(&self, day: u32, name: &str) -> Option<PathBuf> {
+        self.candidates(day, name)
+            .into_iter()
+            .find(|path| path.is_file())
+    }
+
+    pub fn read(&self, day: u32, name: &str) -> Result<String, InputError> {
+        let path = self.path(day, name).ok_or_else(|| InputError::Missing {
+            day,
+            name: name.to_string(),
+            searched: self.candidates(day, name),
+        })?;
+
+        std::fs::read_to_string(&path).map_err(|error| InputError::Io { path, error })
+    }
+
+    /// Like [`InputStore::read`], but a missing input is not an error.
+    pub fn read_optional(&self, day: u32, name: &str) -> Result<Option<String>, InputError> {
+        match self.read(day, name) {
+            Ok(input) => Ok(Some(input)),
+            Err(InputError::Missing { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Default for InputStore {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Missing {
+        day: u32,
+        name: String,
+        searched: Vec<PathBuf>,
+    },
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+}
+
+impl InputError {
+    pub fn is_missing(&self) -> bool {
+        matches!(self, InputError::Missing { .. })
+    }
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::Missing {
+                day,
+                name,
+                searched,
+            } => {
+                let searched: Vec<_> = searched.iter().map(|p| p.display().to_string()).collect();
+                write!(
+                    f,
+                    "no input '{name}' for day {day}, looked in {}",
+                    searched.join(", ")
+                )
+            }
+            InputError::Io { path, error } => {
+                write!(f, "could not read '{}': {error}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidates_test() {
+        let store = InputStore::new("private").with_root("inputs");
+        assert_eq!(
+            store.candidates(3, "input.txt"),
+            [
+                Path::new("private/2023/day3/input.txt"),
+                Path::new("private/day3/input.txt"),
+                Path::new("inputs/2023/day3/input.txt"),
+                Path::new("inputs/day3/input.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_test() {
+        let store = InputStore::new("does-not-exist").with_root(DEFAULT_INPUT_DIR);
+        assert!(store.read(1, "example1.txt").unwrap().starts_with("1abc2"));
+
+        let error = store.read(1, "nothing.txt").unwrap_err();
+        assert!(error.is_missing());
+        assert!(store.read_optional(1, "nothing.txt").unwrap().is_none());
+    }
+}
@@ -0,0 +1,346 @@
+//! Just enough JSON to write benchmark results and read them back as a baseline.
+
+use crate::error::ParseError;
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Keys keep their order, lookups are linear.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns numbers that are whole and fit in a `u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_f64()
+            .filter(|n| n.fract() == 0.0 && *n >= 0.0 && *n <= u64::MAX as f64)
+            .map(|n| n as u64)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl From<u64> for Json {
+    fn from(n: u64) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<u32> for Json {
+    fn from(n: u32) -> Self {
+        Json::Number(n.into())
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter<'_>, s: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Writes compact JSON, use `{:#}` to indent nested values by two spaces.
+impl Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+impl Json {
+    fn write(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let pretty = f.alternate();
+        let newline = |f: &mut std::fmt::Formatter<'_>, depth: usize| {
+            if pretty {
+                write!(f, "\n{:1$}", "", depth * 2)
+            } else {
+                Ok(())
+            }
+        };
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Number(n) if n.is_finite() => write!(f, "{n}"),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(s) => write_string(f, s),
+            Json::Array(values) if values.is_empty() => write!(f, "[]"),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    newline(f, depth + 1)?;
+                    value.write(f, depth + 1)?;
+                }
+                newline(f, depth)?;
+                write!(f, "]")
+            }
+            Json::Object(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    newline(f, depth + 1)?;
+                    write_string(f, key)?;
+                    write!(f, "{}", if pretty { ": " } else { ":" })?;
+                    value.write(f, depth + 1)?;
+                }
+                newline(f, depth)?;
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl FromStr for Json {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(parser.error("the end of the input"));
+        }
+
+        Ok(value)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn error(&self, expected: &str) -> ParseError {
+        let before = &self.input[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = self.pos - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        let found: String = self.rest().chars().take(10).collect();
+        ParseError::new(found, expected).at(line, column)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), ParseError> {
+        match self.eat(token) {
+            true => Ok(()),
+            false => Err(self.error(&format!("'{token}'"))),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, ParseError> {
+        self.skip_whitespace();
+        match self.rest().chars().next() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some('-' | '0'..='9') => self.number(),
+            _ if self.eat("null") => Ok(Json::Null),
+            _ if self.eat("true") => Ok(Json::Bool(true)),
+            _ if self.eat("false") => Ok(Json::Bool(false)),
+            _ => Err(self.error("a JSON value")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, ParseError> {
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        if self.eat("}") {
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(":")?;
+            fields.push((key, self.value()?));
+            if self.eat("}") {
+                return Ok(Json::Object(fields));
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, ParseError> {
+        self.expect("[")?;
+        let mut values = Vec::new();
+        if self.eat("]") {
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            if self.eat("]") {
+                return Ok(Json::Array(values));
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, ParseError> {
+        let len = self
+            .rest()
+            .find(|c: char| !matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9'))
+            .unwrap_or(self.rest().len());
+        let number = self.rest()[..len]
+            .parse()
+            .map_err(|_| self.error("a number"))?;
+        self.pos += len;
+
+        Ok(Json::Number(number))
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        if !self.rest().starts_with('"') {
+            return Err(self.error("a string"));
+        }
+        self.pos += 1;
+        let mut s = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(s);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            let c = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                            let Some(c) = c else {
+                                // at the backslash, like other bad escapes
+                                self.pos += i;
+                                return Err(self.error("a \\u escape outside the surrogate range"));
+                            };
+                            c
+                        }
+                        _ => {
+                            self.pos += i;
+                            return Err(self.error("an escape sequence"));
+                        }
+                    };
+                    s.push(escaped);
+                }
+                c => s.push(c),
+            }
+        }
+        self.pos = self.input.len();
+
+        Err(self.error("'\"'"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_test() {
+        let json = Json::Object(vec![
+            ("day".to_string(), 7u32.into()),
+            ("input".to_string(), "in\"put\n.txt".into()),
+            (
+                "samples".to_string(),
+                Json::Array(vec![Json::Number(1.5), Json::Null, Json::Bool(true)]),
+            ),
+            ("empty".to_string(), Json::Object(vec![])),
+        ]);
+        assert_eq!(
+            json.to_string(),
+            r#"{"day":7,"input":"in\"put\n.txt","samples":[1.5,null,true],"empty":{}}"#
+        );
+        assert_eq!(json.to_string().parse(), Ok(json.clone()));
+        assert_eq!(format!("{json:#}").parse(), Ok(json.clone()));
+        assert_eq!(json.get("day").and_then(Json::as_u64), Some(7));
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let error = "{\n  \"a\": [1, 2,]\n}".parse::<Json>().unwrap_err();
+        assert_eq!((error.line(), error.column()), (Some(2), Some(14)));
+        assert_eq!(error.expected(), "a JSON value");
+
+        assert!("[1] 2".parse::<Json>().is_err());
+        assert!("\"abc".parse::<Json>().is_err());
+
+        let error = r#"{"a": "x\ud800y"}"#.parse::<Json>().unwrap_err();
+        assert_eq!((error.line(), error.column()), (Some(1), Some(9)));
+        assert_eq!(error.text(), "\\ud800y\"}");
+        let error = "[\n  \"\\u12\"]".parse::<Json>().unwrap_err();
+        assert_eq!((error.line(), error.column()), (Some(2), Some(4)));
+    }
+}
@@ -1,617 +1,767 @@
 #![allow(unused)]
 use std::{
     fs::File,
-    io::{self, BufRead, Read},
+    io::{self, BufRead, Read, Write},
 };
 
-mod boat;
-mod calibration;
-mod card;
-mod cubes;
-mod engine;
-mod galaxy;
-mod history;
-mod map;
-mod pipes;
-mod scratch;
-mod soil;
-
-fn input_data(day: &str, file: &str) -> String {
-    format!("inputs/{day}/{file}")
+pub mod answers;
+pub mod bag;
+pub mod bench;
+pub mod boat;
+pub mod calibration;
+pub mod card;
+pub mod cubes;
+pub mod engine;
+pub mod error;
+pub mod galaxy;
+pub mod history;
+pub mod identify;
+pub mod input;
+pub mod json;
+pub mod map;
+pub mod parse;
+pub mod pipes;
+pub mod runner;
+pub mod scanner;
+pub mod scratch;
+pub mod sha256;
+pub mod soil;
+pub mod solution;
+
+pub use error::ParseError;
+pub use solution::Solution;
+
+use parse::{input_lines, parse_lines};
+
+#[cfg(test)]
+fn example_input(day: u32, name: &str) -> String {
+    input::InputStore::from_env()
+        .read(day, name)
+        .unwrap_or_else(|e| panic!("{e}"))
 }
 
-fn read_file_contents(path: &str) -> String {
-    let mut s = String::new();
-    std::fs::File::open(path)
-        .unwrap()
-        .read_to_string(&mut s)
-        .unwrap();
-    s
+/// Private inputs are not committed, tests that need them are skipped when they are missing.
+#[cfg(test)]
+fn private_input(day: u32, name: &str) -> Option<String> {
+    let input = input::InputStore::from_env()
+        .read_optional(day, name)
+        .unwrap_or_else(|e| panic!("{e}"));
+    if input.is_none() {
+        // written straight to stderr, the test harness only captures print macros
+        let _ = writeln!(
+            io::stderr(),
+            "skipped: no private input '{name}' for day {day}"
+        );
+    }
+    input
 }
 
+/// Private answers are only stored as digests in `answers.toml`, so compare against those.
 #[cfg(test)]
-mod day1 {
+fn assert_answer(day: u32, part: u32, name: &str, actual: impl std::fmt::Display) {
+    let answers =
+        answers::Answers::load(answers::DEFAULT_ANSWERS_FILE).unwrap_or_else(|e| panic!("{e}"));
+    let answer = answers
+        .get(day, part, name)
+        .unwrap_or_else(|| panic!("no answer for day {day}, part {part}, '{name}'"));
+    let actual = actual.to_string();
+    assert!(
+        answer.expected().matches(&actual),
+        "wrong answer {actual} for day {day}, part {part}, '{name}'"
+    );
+}
+
+pub mod day1 {
     use super::*;
-    use crate::calibration::calibration_value;
+    use crate::{calibration::calibration_value, solution::Fallible};
 
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day1", name))
-    }
+    pub struct Day1;
 
-    fn solve_part1(input: &str) -> u32 {
-        let sum = input
-            .lines()
-            .map(|l| {
-                let v = calibration_value(&l, false).unwrap();
-                println!("{}: {}", l, v);
-                v
+    /// Sum of the values of every line, failing at the first line without one.
+    fn sum(input: &[(usize, String)], include_words: bool) -> Fallible<u32, ParseError> {
+        let sum = input.iter().try_fold(0_u32, |sum, (number, line)| {
+            let expected = if include_words {
+                "a digit or a number word"
+            } else {
+                "a digit"
+            };
+            let value = calibration_value(line, include_words)
+                .ok_or_else(|| ParseError::new(line.as_str(), expected).at(*number, 1))?;
+            sum.checked_add(value).ok_or_else(|| {
+                ParseError::new(line.as_str(), "a sum that fits in a u32").at(*number, 1)
             })
-            .sum();
-        sum
-    }
+        });
+        Fallible(sum.map_err(|e| e.with_day(Day1::DAY)))
+    }
+
+    impl Solution for Day1 {
+        const DAY: u32 = 1;
+
+        /// Non-empty lines and their line numbers.
+        type Input = Vec<(usize, String)>;
+        type Part1 = Fallible<u32, ParseError>;
+        type Part2 = Fallible<u32, ParseError>;
+
+        /// Lines without a value in either part are an error, lines with only number words
+        /// fail part 1.
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            input_lines(input)
+                .filter(|l| !l.text().trim().is_empty())
+                .map(|l| match calibration_value(l.text(), true) {
+                    Some(_) => Ok((l.number(), l.text().to_string())),
+                    None => Err(l.error(l.text(), "a digit or a number word")),
+                })
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.with_day(Self::DAY))
+        }
 
-    fn solve_part2(input: &str) -> u32 {
-        let sum = input
-            .lines()
-            .map(|l| calibration_value(&l, true).unwrap())
-            .sum();
-        sum
-    }
+        fn part1(input: &Self::Input) -> Self::Part1 {
+            sum(input, false)
+        }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve_part1(&test_file("example1.txt")), 142);
+        fn part2(input: &Self::Input) -> Self::Part2 {
+            sum(input, true)
+        }
     }
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve_part1(&test_file("input.txt")), 54951);
-    }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-    #[test]
-    fn part2_example2() {
-        assert_eq!(solve_part2(&test_file("example2.txt")), 281);
-    }
+        fn test_file(name: &str) -> String {
+            example_input(1, name)
+        }
+
+        fn solve_part1(input: &str) -> u32 {
+            Day1::part1(&Day1::parse(input).unwrap()).0.unwrap()
+        }
+
+        fn solve_part2(input: &str) -> u32 {
+            Day1::part2(&Day1::parse(input).unwrap()).0.unwrap()
+        }
+
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_part1(&test_file("example1.txt")), 142);
+        }
 
-    #[test]
-    fn part2() {
-        assert_eq!(solve_part2(&test_file("input.txt")), 55218);
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(1, "input.txt") else {
+                return;
+            };
+            assert_answer(1, 1, "input.txt", solve_part1(&input));
+        }
+
+        #[test]
+        fn errors() {
+            assert_eq!(solve_part1("1abc2\n\npqr3stu8vwx\n"), 12 + 38);
+            let error = Day1::parse("1abc2\nxyz\n").unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "day 1, line 2, column 1: expected a digit or a number word, found 'xyz'"
+            );
+            let input = Day1::parse(&test_file("example2.txt")).unwrap();
+            assert_eq!(
+                Day1::part1(&input).to_string(),
+                "error: day 1, line 2, column 1: expected a digit, found 'eightwothree'"
+            );
+        }
+
+        #[test]
+        fn part2_example2() {
+            assert_eq!(solve_part2(&test_file("example2.txt")), 281);
+        }
+
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(1, "input.txt") else {
+                return;
+            };
+            assert_answer(1, 2, "input.txt", solve_part2(&input));
+        }
     }
 }
 
-#[cfg(test)]
-mod day2 {
+pub mod day2 {
     use super::*;
-    use cubes::CubeSet;
-
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day2", name))
-    }
-
-    fn parse_game(line: &str) -> (u32, Vec<CubeSet>) {
-        let (game_id, game_grabs) = line.split_once(": ").unwrap();
-        let id: u32 = game_id
-            .strip_prefix("Game ")
-            .map(|id| id.parse().unwrap())
-            .unwrap();
-        let sets: Vec<CubeSet> = game_grabs
-            .split("; ")
-            .map(|grab| {
-                let mut cube_set = CubeSet::default();
-                // 3 blue, 4 red
-                let parts = grab.split(", ");
-                for part in parts {
-                    // 3 blue
-                    let (num, color) = part.split_once(" ").unwrap();
-                    let num = num.parse().unwrap();
-                    match color {
-                        "red" => cube_set.red = num,
-                        "green" => cube_set.green = num,
-                        "blue" => cube_set.blue = num,
-                        _ => unreachable!(),
-                    }
-                }
-                cube_set
-            })
-            .collect();
+    use crate::cubes::{CubeSet, Game};
 
-        (id, sets)
-    }
+    pub const PALETTE: [&str; 3] = ["red", "green", "blue"];
 
-    fn solve_part1(input: &str) -> u32 {
-        let mut sum_of_possible_ids = 0;
-        let limit = CubeSet {
-            red: 12,
-            green: 13,
-            blue: 14,
-        };
-        for line in input.lines() {
-            let (id, cube_set) = parse_game(&line);
-            if !cube_set.iter().any(|cs| !cubes::is_possible(limit, *cs)) {
-                sum_of_possible_ids += id;
-            }
-        }
-        sum_of_possible_ids
-    }
+    pub struct Day2;
 
-    fn solve_part2(input: &str) -> u32 {
-        input
-            .lines()
-            .map(|line| {
-                let (id, grabs) = parse_game(&line);
-                let red_required = grabs.iter().map(|g| g.red).max().unwrap_or(0);
-                let green_required = grabs.iter().map(|g| g.green).max().unwrap_or(0);
-                let blue_required = grabs.iter().map(|g| g.blue).max().unwrap_or(0);
-                red_required * green_required * blue_required
-            })
-            .sum()
+    /// One game per non-empty line, with only the colours in `palette` if there is one.
+    pub fn parse_games(input: &str, palette: Option<&[&str]>) -> Result<Vec<Game>, ParseError> {
+        input_lines(input)
+            .filter(|l| !l.text().trim().is_empty())
+            .map(|l| Game::parse(l, palette))
+            .collect()
     }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve_part1(&test_file("example1.txt")), 8);
-    }
+    impl Solution for Day2 {
+        const DAY: u32 = 2;
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve_part1(&test_file("input.txt")), 2476);
-    }
+        type Input = Vec<Game>;
+        type Part1 = u32;
+        type Part2 = u64;
 
-    #[test]
-    fn part2_example1() {
-        assert_eq!(solve_part2(&test_file("example1.txt")), 2286);
-    }
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            parse_games(input, Some(&PALETTE)).map_err(|e| e.with_day(Self::DAY))
+        }
+
+        fn part1(input: &Self::Input) -> u32 {
+            let limit = CubeSet::new()
+                .with("red", 12)
+                .with("green", 13)
+                .with("blue", 14);
+            input
+                .iter()
+                .filter(|game| game.is_possible_with(&limit))
+                .map(|game| game.id())
+                .sum()
+        }
 
-    #[test]
-    fn part2() {
-        assert_eq!(solve_part2(&test_file("input.txt")), 54911);
+        fn part2(input: &Self::Input) -> u64 {
+            input
+                .iter()
+                .map(|game| game.minimum_bag().power_over(PALETTE))
+                .sum()
+        }
     }
-}
 
-#[cfg(test)]
-mod day3 {
-    use super::*;
-    use crate::engine::{Schematic, SchematicSymbol};
-    use simple_grid::Grid;
-
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day3", name))
-    }
-
-    fn parse_schematic(input: &str) -> Schematic {
-        let lines: Vec<&str> = input.lines().collect();
-        let w = lines[0].len();
-        let h = lines.len();
-        let chars: Vec<SchematicSymbol> = lines
-            .join("")
-            .chars()
-            .map(|c| match c.to_digit(10) {
-                Some(digit) => SchematicSymbol::Number(digit),
-                None if c == '.' => SchematicSymbol::Period,
-                None => SchematicSymbol::Symbol(c),
-            })
-            .collect();
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-        Schematic::new(Grid::new(w, h, chars))
-    }
+        fn test_file(name: &str) -> String {
+            example_input(2, name)
+        }
 
-    fn solve_part1(input: &str) -> u32 {
-        let schematic = parse_schematic(input);
-        let included_parts = schematic.part_numbers();
-        included_parts.into_iter().sum()
-    }
+        fn solve_part1(input: &str) -> u32 {
+            Day2::part1(&Day2::parse(input).unwrap())
+        }
 
-    fn solve_part2(input: &str) -> u32 {
-        let schematic = parse_schematic(input);
-        let gears = schematic.gear_ratios();
-        gears.into_iter().sum()
-    }
+        fn solve_part2(input: &str) -> u64 {
+            Day2::part2(&Day2::parse(input).unwrap())
+        }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve_part1(&test_file("example1.txt")), 4361);
-    }
+        #[test]
+        fn parse_error() {
+            let error = Day2::parse("Game 1: 3 blue\nGame 2: 1 red, 2 purple").unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "day 2, line 2, column 18: expected 'red', 'green' or 'blue', found 'purple'"
+            );
+        }
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve_part1(&test_file("input.txt")), 546563);
-    }
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_part1(&test_file("example1.txt")), 8);
+        }
 
-    #[test]
-    fn part2_example1() {
-        assert_eq!(solve_part2(&test_file("example1.txt")), 467835);
-    }
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(2, "input.txt") else {
+                return;
+            };
+            assert_answer(2, 1, "input.txt", solve_part1(&input));
+        }
 
-    #[test]
-    fn part2() {
-        assert_eq!(solve_part2(&test_file("input.txt")), 91031374);
+        #[test]
+        fn part2_example1() {
+            assert_eq!(solve_part2(&test_file("example1.txt")), 2286);
+        }
+
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(2, "input.txt") else {
+                return;
+            };
+            assert_answer(2, 2, "input.txt", solve_part2(&input));
+        }
     }
 }
 
-#[cfg(test)]
-mod day4 {
+pub mod day3 {
     use super::*;
-    use scratch::Card;
-    use std::collections::{HashMap, HashSet};
-
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day4", name))
-    }
-
-    fn parse_card_from_line(line: &str) -> Card {
-        // Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-        let (id, numbers) = line
-            .trim()
-            .strip_prefix("Card ")
-            .unwrap()
-            .trim()
-            .split_once(": ")
-            .unwrap();
-        let id = id.parse().unwrap();
-        // numbers = 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-        let (winning, hand) = numbers.split_once(" | ").unwrap();
-        let winning: HashSet<u32> = winning
-            .split_whitespace()
-            .map(|p| p.parse().unwrap())
-            .collect();
-        let hand: HashSet<u32> = hand
-            .split_whitespace()
-            .map(|p| p.parse().unwrap())
-            .collect();
+    use crate::engine::Schematic;
 
-        Card::new(id, winning, hand)
-    }
+    pub struct Day3;
 
-    fn parse_cards(input: &str) -> Vec<Card> {
-        let cards = input
-            .lines()
-            .filter(|l| !l.trim().is_empty())
-            .map(|l| parse_card_from_line(&l))
-            .collect();
-        cards
-    }
+    impl Solution for Day3 {
+        const DAY: u32 = 3;
 
-    fn solve_part1(input: &str) -> u32 {
-        let cards = parse_cards(input);
-        let mut value = 0;
-        for card in cards {
-            let winning_numbers = card.winning_numbers().count() as u32;
-            if winning_numbers > 0 {
-                value += 2_u32.pow(winning_numbers - 1);
-            }
+        type Input = Schematic;
+        type Part1 = u64;
+        type Part2 = u128;
+
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            input.parse().map_err(|e: ParseError| e.with_day(Self::DAY))
         }
 
-        value
+        fn part1(schematic: &Schematic) -> u64 {
+            let included_parts = schematic.part_numbers();
+            included_parts.into_iter().sum()
+        }
+
+        fn part2(schematic: &Schematic) -> u128 {
+            let gears = schematic.gear_ratios();
+            gears.into_iter().sum()
+        }
     }
 
-    fn solve_part2(input: &str) -> u64 {
-        let cards = parse_cards(input);
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-        Card::total_scratchcards(&cards)
-    }
+        fn test_file(name: &str) -> String {
+            example_input(3, name)
+        }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve_part1(&test_file("example1.txt")), 13);
-    }
+        fn solve_part1(input: &str) -> u64 {
+            Day3::part1(&Day3::parse(input).unwrap())
+        }
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve_part1(&test_file("input.txt")), 32609);
-    }
+        fn solve_part2(input: &str) -> u128 {
+            Day3::part2(&Day3::parse(input).unwrap())
+        }
 
-    #[test]
-    fn part2_example1() {
-        assert_eq!(solve_part2(&test_file("example1.txt")), 30);
-    }
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_part1(&test_file("example1.txt")), 4361);
+        }
 
-    #[test]
-    fn part2() {
-        assert_eq!(solve_part2(&test_file("input.txt")), 14624680);
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(3, "input.txt") else {
+                return;
+            };
+            assert_answer(3, 1, "input.txt", solve_part1(&input));
+        }
+
+        #[test]
+        fn part2_example1() {
+            assert_eq!(solve_part2(&test_file("example1.txt")), 467835);
+        }
+
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(3, "input.txt") else {
+                return;
+            };
+            assert_answer(3, 2, "input.txt", solve_part2(&input));
+        }
     }
 }
 
-#[cfg(test)]
-mod day5 {
+pub mod day4 {
     use super::*;
-    use crate::soil::{Almanac, Map, Mappings, Range};
+    use crate::{
+        parse::InputLine,
+        scratch::{Card, ScratchcardError},
+        solution::Fallible,
+    };
+    use std::collections::HashMap;
 
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day5", name))
-    }
+    pub struct Day4;
 
-    fn parse_seeds_from_line(line: &str) -> Vec<u64> {
-        // seeds: 1 2 3
-        line.strip_prefix("seeds: ")
-            .unwrap()
-            .split(" ")
-            .map(|p| p.parse().unwrap())
-            .collect()
+    /// The id of a card as written on its line, for pointing errors at it.
+    fn id_text(line: InputLine<'_>) -> &str {
+        let text = line.text();
+        let card = text.split(':').next().unwrap_or(text);
+        card.trim().trim_start_matches("Card").trim_start()
     }
 
-    fn parse_maps_from_lines(lines: &[&str]) -> Mappings {
-        let mut maps = Vec::with_capacity(lines.len());
-        for line in &lines[1..] {
-            let parts: Vec<u64> = line.split(" ").map(|p| p.parse().unwrap()).collect();
-            assert_eq!(parts.len(), 3);
-            maps.push(Map::new(parts[0], parts[1], parts[2]));
+    /// One card per non-empty line, with unique ids. Gaps between ids are only an error if a
+    /// card wins a copy of a missing one, which part 2 reports.
+    pub fn parse_cards(input: &str) -> Result<Vec<Card>, ParseError> {
+        let mut cards = Vec::new();
+        let mut lines = HashMap::new();
+        for line in input_lines(input).filter(|l| !l.text().trim().is_empty()) {
+            let card: Card = line
+                .text()
+                .parse()
+                .map_err(|e: ParseError| e.offset_lines(line.number() - 1))?;
+            if let Some(first) = lines.insert(card.id(), line) {
+                let expected = format!("a card id not already used on line {}", first.number());
+                return Err(line.error(id_text(line), &expected));
+            }
+            cards.push(card);
         }
-
-        Mappings::new(lines[0].to_string(), maps)
+        Ok(cards)
     }
 
-    fn parse_almanac(input: &str) -> (Vec<u64>, Almanac) {
-        let lines: Vec<&str> = input.lines().collect();
+    impl Solution for Day4 {
+        const DAY: u32 = 4;
 
-        let seeds = parse_seeds_from_line(&lines[0]);
-        let map_lines: Vec<_> = lines[2..].split(|l| l.trim().is_empty()).collect();
+        type Input = Vec<Card>;
+        type Part1 = Fallible<u128, ScratchcardError>;
+        type Part2 = Fallible<u64, ScratchcardError>;
 
-        let mut map_tiers = Vec::with_capacity(map_lines.len());
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            parse_cards(input).map_err(|e| e.with_day(Self::DAY))
+        }
 
-        for l in map_lines {
-            map_tiers.push(parse_maps_from_lines(l));
+        fn part1(cards: &Self::Input) -> Self::Part1 {
+            let points = cards.iter().try_fold(0_u128, |total, card| {
+                let points = card
+                    .points()
+                    .ok_or(ScratchcardError::TooManyPoints { card: card.id() })?;
+                Ok(total + u128::from(points))
+            });
+            Fallible(points)
         }
 
-        (seeds, Almanac::new(map_tiers))
+        fn part2(cards: &Self::Input) -> Self::Part2 {
+            Fallible(Card::total_scratchcards(cards))
+        }
     }
 
-    fn solve_part1(input: &str) -> u64 {
-        let (seeds, almanac) = parse_almanac(input);
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-        seeds
-            .into_iter()
-            .flat_map(|seed| almanac.convert(Range::new(seed, seed + 1)))
-            .map(|r| r.start())
-            .min()
-            .unwrap()
-    }
+        fn test_file(name: &str) -> String {
+            example_input(4, name)
+        }
 
-    fn solve_part2(input: &str) -> u64 {
-        let (seeds, almanac) = parse_almanac(input);
-        seeds
-            .chunks(2)
-            .into_iter()
-            .flat_map(|w| almanac.convert(Range::new(w[0], w[0] + w[1])))
-            .map(|r| r.start())
-            .min()
-            .unwrap()
-    }
+        fn solve_part1(input: &str) -> u128 {
+            Day4::part1(&Day4::parse(input).unwrap()).0.unwrap()
+        }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve_part1(&test_file("example1.txt")), 35);
-    }
+        fn solve_part2(input: &str) -> u64 {
+            Day4::part2(&Day4::parse(input).unwrap()).0.unwrap()
+        }
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve_part1(&test_file("input.txt")), 313045984);
-    }
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_part1(&test_file("example1.txt")), 13);
+        }
 
-    #[test]
-    fn part2_example1() {
-        assert_eq!(solve_part2(&test_file("example1.txt")), 46);
-    }
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(4, "input.txt") else {
+                return;
+            };
+            assert_answer(4, 1, "input.txt", solve_part1(&input));
+        }
 
-    #[test]
-    fn part2() {
-        assert_eq!(solve_part2(&test_file("input.txt")), 20283860);
+        #[test]
+        fn part2_example1() {
+            assert_eq!(solve_part2(&test_file("example1.txt")), 30);
+        }
+
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(4, "input.txt") else {
+                return;
+            };
+            assert_answer(4, 2, "input.txt", solve_part2(&input));
+        }
+
+        #[test]
+        fn parse_error() {
+            let error = Day4::parse("Card 1: 1 | 1\nCard 2: 1 | 2\n\nCard  1: 3 | 3").unwrap_err();
+            assert_eq!(
+                (error.line(), error.column(), error.text()),
+                (Some(4), Some(7), "1")
+            );
+            assert_eq!(error.expected(), "a card id not already used on line 1");
+        }
+
+        #[test]
+        fn part_errors() {
+            // a gap no card wins a copy of is fine
+            let cards = Day4::parse("Card 3: 1 | 1\nCard 1: 1 | 2\nCard 4: 1 | 2").unwrap();
+            assert_eq!(Day4::part2(&cards).to_string(), "4");
+            let cards = Day4::parse("Card 1: 1 | 1\nCard 3: 1 | 2").unwrap();
+            assert_eq!(
+                Day4::part2(&cards).to_string(),
+                "error: card 1 wins card 2, which doesn't exist"
+            );
+
+            let numbers: Vec<_> = (1..=65).map(|n| n.to_string()).collect();
+            let numbers = numbers.join(" ");
+            let cards = Day4::parse(&format!("Card 1: {numbers} | {numbers}")).unwrap();
+            assert_eq!(
+                Day4::part1(&cards).to_string(),
+                "error: card 1 is worth more points than fit in a u64"
+            );
+            assert_eq!(Day4::part2(&cards).to_string(), "1");
+            let numbers = &numbers[..numbers.len() - " 65".len()];
+            let card = format!("Card 1: {numbers} | {numbers}\nCard 2: 1 | 1");
+            assert_eq!(solve_part1(&card), (1 << 63) + 1);
+
+            let cards: Vec<_> = (1..=120)
+                .map(|id| format!("Card {id}: 1 2 | 1 2"))
+                .collect();
+            let cards = Day4::parse(&cards.join("\n")).unwrap();
+            assert_eq!(
+                Day4::part2(&cards).to_string(),
+                "error: card 92 has more copies than fit in a u64"
+            );
+        }
     }
 }
 
-#[cfg(test)]
-mod day6 {
+pub mod day5 {
     use super::*;
-    use crate::boat::Race;
+    use crate::{
+        parse::InputLine,
+        soil::{Almanac, Range},
+    };
+
+    pub struct Day5;
+
+    /// An even number of seeds, read as single seeds or as pairs of a start and a length.
+    /// Either way every range has to end within 64 bits.
+    fn parse_seeds_from_line(line: InputLine) -> Result<Vec<u64>, ParseError> {
+        // seeds: 1 2 3 4
+        let parts: Vec<_> = line
+            .strip_prefix(line.text(), "seeds: ")?
+            .split(' ')
+            .collect();
+        let mut seeds = Vec::with_capacity(parts.len());
+        for part in &parts {
+            let seed: u64 = line.parse(part, "a seed number")?;
+            if seed == u64::MAX {
+                return Err(line.error(part, "a seed number below 2^64 - 1"));
+            }
+            seeds.push(seed);
+        }
+        if seeds.len() % 2 != 0 {
+            let end = &line.text()[line.text().len()..];
+            return Err(line.error(end, "a range length after every seed number"));
+        }
+        for (pair, len) in seeds.chunks(2).zip(parts.iter().skip(1).step_by(2)) {
+            if pair[0].checked_add(pair[1]).is_none() {
+                return Err(line.error(len, "a range that ends within 64 bits"));
+            }
+        }
 
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day6", name))
+        Ok(seeds)
     }
 
-    fn parse_races_part1(input: &str) -> Vec<Race> {
-        let lines: Vec<&str> = input.lines().collect();
-        assert_eq!(lines.len(), 2);
-
-        let times: Vec<u64> = lines[0]
-            .strip_prefix("Time:")
-            .unwrap()
-            .trim()
-            .split_whitespace()
-            .map(|p| p.parse().unwrap())
-            .collect();
-
-        let records: Vec<u64> = lines[1]
-            .strip_prefix("Distance:")
-            .unwrap()
-            .trim()
-            .split_whitespace()
-            .map(|p| p.parse().unwrap())
-            .collect();
+    pub fn parse_almanac(input: &str) -> Result<(Vec<u64>, Almanac), ParseError> {
+        let lines: Vec<InputLine> = input_lines(input).collect();
 
-        assert_eq!(times.len(), records.len());
+        let first = lines
+            .first()
+            .ok_or_else(|| ParseError::missing_line(1, "'seeds: '"))?;
+        let seeds = parse_seeds_from_line(*first)?;
+        let almanac = Almanac::from_lines(lines.get(2..).unwrap_or_default())?;
 
-        times
-            .into_iter()
-            .zip(records.into_iter())
-            .map(|(time, record)| Race::new(time, record))
-            .collect()
+        Ok((seeds, almanac))
     }
 
-    fn parse_race_part2(input: &str) -> Race {
-        let lines: Vec<&str> = input.lines().collect();
-        assert_eq!(lines.len(), 2);
-
-        let parts: Vec<&str> = lines[0]
-            .strip_prefix("Time:")
-            .unwrap()
-            .trim()
-            .split_whitespace()
-            .collect();
+    impl Solution for Day5 {
+        const DAY: u32 = 5;
 
-        let time: u64 = parts.join("").parse().unwrap();
+        type Input = (Vec<u64>, Almanac);
+        type Part1 = u64;
+        type Part2 = u64;
 
-        let parts: Vec<&str> = lines[1]
-            .strip_prefix("Distance:")
-            .unwrap()
-            .trim()
-            .split_whitespace()
-            .collect();
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            parse_almanac(input).map_err(|e| e.with_day(Self::DAY))
+        }
 
-        let record: u64 = parts.join("").parse().unwrap();
+        fn part1((seeds, almanac): &Self::Input) -> u64 {
+            let seeds: Vec<_> = seeds
+                .iter()
+                .map(|&s| Range::new(s, s.checked_add(1).expect("checked when parsing")))
+                .collect();
+            almanac.lowest(&seeds).expect("there are seeds").0
+        }
 
-        Race::new(time, record)
+        fn part2((seeds, almanac): &Self::Input) -> u64 {
+            let seeds: Vec<_> = seeds
+                .chunks(2)
+                .map(|w| Range::new(w[0], w[0].checked_add(w[1]).expect("checked when parsing")))
+                .collect();
+            almanac.lowest(&seeds).expect("there are seeds").0
+        }
     }
 
-    fn solve_part1(input: &str) -> u64 {
-        let races = parse_races_part1(input);
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-        let mut product = 1;
-        for race in races {
-            let mut ways_to_beat = 0;
-            for held in 1..race.time() {
-                if race.beats_record(held) {
-                    ways_to_beat += 1;
-                }
-            }
-            product *= ways_to_beat;
+        fn test_file(name: &str) -> String {
+            example_input(5, name)
         }
 
-        product
-    }
-
-    fn solve_part2(input: &str) -> u32 {
-        let race = parse_race_part2(input);
+        fn solve_part1(input: &str) -> u64 {
+            Day5::part1(&Day5::parse(input).unwrap())
+        }
 
-        let mut ways_to_beat = 0;
-        let mid = race.time() / 2;
-        for i in 0.. {
-            let mut beat_record = false;
-            if race.beats_record(mid + i + 1) {
-                // go right
-                beat_record = true;
-                ways_to_beat += 1;
-            }
-            if race.beats_record(mid - i) {
-                // go left
-                beat_record = true;
-                ways_to_beat += 1;
-            }
-            if !beat_record {
-                break;
-            }
+        fn solve_part2(input: &str) -> u64 {
+            Day5::part2(&Day5::parse(input).unwrap())
         }
 
-        ways_to_beat
-    }
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_part1(&test_file("example1.txt")), 35);
+        }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve_part1(&test_file("example1.txt")), 288);
-    }
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(5, "input.txt") else {
+                return;
+            };
+            assert_answer(5, 1, "input.txt", solve_part1(&input));
+        }
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve_part1(&test_file("input.txt")), 1084752);
-    }
+        #[test]
+        fn part2_example1() {
+            assert_eq!(solve_part2(&test_file("example1.txt")), 46);
+        }
 
-    #[test]
-    fn part2_example1() {
-        assert_eq!(solve_part2(&test_file("example1.txt")), 71503);
-    }
+        #[test]
+        fn parse_error() {
+            let almanac = "\n\nseed-to-soil map:\n50 98 2";
+            let error = Day5::parse(&format!("seeds: 79 14 55{almanac}")).unwrap_err();
+            assert_eq!((error.line(), error.column()), (Some(1), Some(16)));
+            assert_eq!(error.expected(), "a range length after every seed number");
+
+            let max = u64::MAX;
+            let error = Day5::parse(&format!("seeds: 1 {max}{almanac}")).unwrap_err();
+            assert_eq!((error.line(), error.column()), (Some(1), Some(10)));
+            let error = Day5::parse(&format!("seeds: 2 {}{almanac}", max - 1)).unwrap_err();
+            assert_eq!(error.expected(), "a range that ends within 64 bits");
+            let (seeds, _) = Day5::parse(&format!("seeds: 2 {}{almanac}", max - 2)).unwrap();
+            assert_eq!(seeds, [2, max - 2]);
+        }
 
-    #[test]
-    fn part2() {
-        assert_eq!(solve_part2(&test_file("input.txt")), 28228952);
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(5, "input.txt") else {
+                return;
+            };
+            assert_answer(5, 2, "input.txt", solve_part2(&input));
+        }
     }
 }
 
-#[cfg(test)]
-mod day7 {
+pub mod day6 {
     use super::*;
-    use crate::card::{Card, Cards};
-    use std::cmp::Ordering;
+    use crate::boat::{Race, Races};
 
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day7", name))
-    }
+    pub struct Day6;
 
-    fn parse_cards(input: &str) -> Vec<Cards> {
-        let mut cards = Vec::new();
-        for line in input.lines() {
-            let parts: Vec<_> = line.split(" ").collect();
+    impl Solution for Day6 {
+        const DAY: u32 = 6;
 
-            debug_assert_eq!(parts.len(), 2);
+        type Input = (Races, Race);
+        type Part1 = u64;
+        type Part2 = u32;
 
-            let mut chars = parts[0].chars();
-            let bid = parts[1].parse().unwrap();
-            cards.push(Cards::new(
-                [
-                    Card::try_from(chars.next().unwrap()).unwrap(),
-                    Card::try_from(chars.next().unwrap()).unwrap(),
-                    Card::try_from(chars.next().unwrap()).unwrap(),
-                    Card::try_from(chars.next().unwrap()).unwrap(),
-                    Card::try_from(chars.next().unwrap()).unwrap(),
-                ],
-                bid,
-            ));
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            let races = input
+                .parse()
+                .map_err(|e: ParseError| e.with_day(Self::DAY))?;
+            let race = input
+                .parse()
+                .map_err(|e: ParseError| e.with_day(Self::DAY))?;
+            Ok((races, race))
         }
 
-        cards
-    }
+        fn part1((races, _): &Self::Input) -> u64 {
+            let mut product = 1;
+            for race in races.races() {
+                let mut ways_to_beat = 0;
+                for held in 1..race.time() {
+                    if race.beats_record(held) {
+                        ways_to_beat += 1;
+                    }
+                }
+                product *= ways_to_beat;
+            }
 
-    fn get_value_of_all_cards(cards: &[Cards]) -> u32 {
-        let mut total = 0;
-        for i in 0..cards.len() {
-            let bid = cards[i].bid();
-            total += bid * (i as u32 + 1);
+            product
+        }
+
+        fn part2((_, race): &Self::Input) -> u32 {
+            let mut ways_to_beat = 0;
+            let mid = race.time() / 2;
+            for i in 0.. {
+                let mut beat_record = false;
+                if race.beats_record(mid + i + 1) {
+                    // go right
+                    beat_record = true;
+                    ways_to_beat += 1;
+                }
+                if race.beats_record(mid - i) {
+                    // go left
+                    beat_record = true;
+                    ways_to_beat += 1;
+                }
+                if !beat_record {
+                    break;
+                }
+            }
+
+            ways_to_beat
         }
-        total
     }
 
-    fn solve_part1(input: &str) -> u32 {
-        let mut cards = parse_cards(input);
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-        cards.sort();
+        fn test_file(name: &str) -> String {
+            example_input(6, name)
+        }
+
+        fn solve_part1(input: &str) -> u64 {
+            Day6::part1(&Day6::parse(input).unwrap())
+        }
 
-        get_value_of_all_cards(&cards)
+        fn solve_part2(input: &str) -> u32 {
+            Day6::part2(&Day6::parse(input).unwrap())
+        }
+
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_part1(&test_file("example1.txt")), 288);
+        }
+
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(6, "input.txt") else {
+                return;
+            };
+            assert_answer(6, 1, "input.txt", solve_part1(&input));
+        }
+
+        #[test]
+        fn part2_example1() {
+            assert_eq!(solve_part2(&test_file("example1.txt")), 71503);
+        }
+
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(6, "input.txt") else {
+                return;
+            };
+            assert_answer(6, 2, "input.txt", solve_part2(&input));
+        }
     }
+}
 
-    fn solve_part2(input: &str) -> u32 {
-        let all_cards = parse_cards(input);
-        let mut swapped_out_optimally: Vec<_> = all_cards
-            .into_iter()
-            .map(|cards| swap_out_joker(cards))
-            .collect();
+pub mod day7 {
+    use super::*;
+    use crate::card::{Card, Cards};
+    use std::cmp::Ordering;
 
-        swapped_out_optimally.sort_by(|(cards_a, joker_pos_a), (cards_b, joker_pos_b)| {
-            if cards_a.hand_type().numeric_value() == cards_b.hand_type().numeric_value() {
-                // compare by value, J is 1
-                for i in 0..5 {
-                    let card_a = if joker_pos_a[i] {
-                        Card::new(1).unwrap()
-                    } else {
-                        cards_a.cards()[i]
-                    };
-                    let card_b = if joker_pos_b[i] {
-                        Card::new(1).unwrap()
-                    } else {
-                        cards_b.cards()[i]
-                    };
-                    let ord = card_a.cmp(&card_b);
-                    if ord.is_ne() {
-                        return ord;
-                    }
-                }
-                return Ordering::Equal;
-            } else {
-                cards_a.cmp(cards_b)
-            }
-        });
+    pub struct Day7;
 
-        let cards: Vec<Cards> = swapped_out_optimally
-            .into_iter()
-            .map(|(cards, _)| cards)
-            .collect();
-        get_value_of_all_cards(&cards)
+    fn get_value_of_all_cards(cards: &[Cards]) -> u32 {
+        let mut total = 0;
+        for (i, cards) in cards.iter().enumerate() {
+            total += cards.bid() * (i as u32 + 1);
+        }
+        total
     }
 
     fn swap_out_joker(cards: Cards) -> (Cards, [bool; 5]) {
@@ -653,101 +803,123 @@ mod day7 {
         (candidates[candidates.len() - 1], joker_positions)
     }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve_part1(&test_file("example1.txt")), 6440);
-    }
+    impl Solution for Day7 {
+        const DAY: u32 = 7;
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve_part1(&test_file("input.txt")), 247823654);
-    }
+        type Input = Vec<Cards>;
+        type Part1 = u32;
+        type Part2 = u32;
 
-    #[test]
-    fn part2_example1() {
-        assert_eq!(solve_part2(&test_file("example1.txt")), 5905);
-    }
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            parse_lines(input).map_err(|e| e.with_day(Self::DAY))
+        }
 
-    #[test]
-    fn part2() {
-        assert_eq!(solve_part2(&test_file("input.txt")), 245461700);
-    }
-}
+        fn part1(cards: &Self::Input) -> u32 {
+            let mut cards = cards.clone();
 
-#[cfg(test)]
-mod day8 {
-    use super::*;
-    use crate::map::{Direction, Map, Node};
-    use std::{collections::HashMap, fs::DirEntry};
+            cards.sort();
 
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day8", name))
-    }
+            get_value_of_all_cards(&cards)
+        }
 
-    fn parse_directions(input: &str) -> Vec<Direction> {
-        input
-            .chars()
-            .map(|c| match c {
-                'R' => Direction::Right,
-                'L' => Direction::Left,
-                _ => panic!(),
-            })
-            .collect()
+        fn part2(all_cards: &Self::Input) -> u32 {
+            let mut swapped_out_optimally: Vec<_> = all_cards
+                .iter()
+                .map(|&cards| swap_out_joker(cards))
+                .collect();
+
+            swapped_out_optimally.sort_by(|(cards_a, joker_pos_a), (cards_b, joker_pos_b)| {
+                if cards_a.hand_type().numeric_value() == cards_b.hand_type().numeric_value() {
+                    // compare by value, J is 1
+                    for i in 0..5 {
+                        let card_a = if joker_pos_a[i] {
+                            Card::new(1).unwrap()
+                        } else {
+                            cards_a.cards()[i]
+                        };
+                        let card_b = if joker_pos_b[i] {
+                            Card::new(1).unwrap()
+                        } else {
+                            cards_b.cards()[i]
+                        };
+                        let ord = card_a.cmp(&card_b);
+                        if ord.is_ne() {
+                            return ord;
+                        }
+                    }
+                    Ordering::Equal
+                } else {
+                    cards_a.cmp(cards_b)
+                }
+            });
+
+            let cards: Vec<Cards> = swapped_out_optimally
+                .into_iter()
+                .map(|(cards, _)| cards)
+                .collect();
+            get_value_of_all_cards(&cards)
+        }
     }
 
-    fn parse_map(lines: &[&str]) -> Option<Map> {
-        let mut nodes = HashMap::new();
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-        for line in lines {
-            let (id, lr) = line.split_once(" = ").unwrap();
-            let (left, right) = lr.strip_prefix("(")?.strip_suffix(")")?.split_once(", ")?;
-            let id: [char; 3] = {
-                let mut chars = id.chars();
-                [chars.next()?, chars.next()?, chars.next()?]
-            };
-            let left: [char; 3] = {
-                let mut chars = left.chars();
-                [chars.next()?, chars.next()?, chars.next()?]
-            };
-            let right: [char; 3] = {
-                let mut chars = right.chars();
-                [chars.next()?, chars.next()?, chars.next()?]
-            };
+        fn test_file(name: &str) -> String {
+            example_input(7, name)
+        }
 
-            nodes.insert(id, Node(left, right));
+        fn solve_part1(input: &str) -> u32 {
+            Day7::part1(&Day7::parse(input).unwrap())
         }
-        Some(Map::new(nodes))
-    }
 
-    fn solve_part1(input: &str) -> u64 {
-        let lines: Vec<_> = input.lines().collect();
-        let directions = parse_directions(lines[0]);
-        let map = parse_map(&lines[2..]).unwrap();
+        fn solve_part2(input: &str) -> u32 {
+            Day7::part2(&Day7::parse(input).unwrap())
+        }
 
-        map.path_length(['A'; 3], ['Z'; 3], &directions)
-    }
+        #[test]
+        fn parse_error() {
+            let error = Day7::parse("32T3K 765\nT55X5 684").unwrap_err();
+            assert_eq!((error.line(), error.column()), (Some(2), Some(4)));
+            assert_eq!(error.text(), "X");
 
-    fn solve_part2(input: &str) -> u64 {
-        let lines: Vec<_> = input.lines().collect();
-        let directions = parse_directions(lines[0]);
-        let map = parse_map(&lines[2..]).unwrap();
+            let error = Day7::parse("32T3 765").unwrap_err();
+            assert_eq!(error.expected(), "a hand of five cards");
+        }
 
-        let mut lengths = Vec::new();
-        for &id in map.nodes().keys() {
-            if id[2] == 'A' {
-                let mut current = id;
-                for (steps, &dir) in (0..).zip(directions.iter().cycle()) {
-                    if current[2] == 'Z' {
-                        lengths.push(steps);
-                        break;
-                    }
-                    current = map.step_once(current, dir);
-                }
-            }
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_part1(&test_file("example1.txt")), 6440);
         }
 
-        lcm_of_n(&lengths)
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(7, "input.txt") else {
+                return;
+            };
+            assert_answer(7, 1, "input.txt", solve_part1(&input));
+        }
+
+        #[test]
+        fn part2_example1() {
+            assert_eq!(solve_part2(&test_file("example1.txt")), 5905);
+        }
+
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(7, "input.txt") else {
+                return;
+            };
+            assert_answer(7, 2, "input.txt", solve_part2(&input));
+        }
     }
+}
+
+pub mod day8 {
+    use super::*;
+    use crate::map::{Direction, Map};
+
+    pub struct Day8;
 
     fn gcd(a: u64, b: u64) -> u64 {
         if b == 0 {
@@ -771,196 +943,278 @@ mod day8 {
         result
     }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve_part1(&test_file("example1.txt")), 2);
-    }
+    impl Solution for Day8 {
+        const DAY: u32 = 8;
+
+        type Input = (Vec<Direction>, Map);
+        type Part1 = u64;
+        type Part2 = u64;
+
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            let lines: Vec<_> = input_lines(input).collect();
+            let parsed = lines
+                .first()
+                .ok_or_else(|| ParseError::missing_line(1, "'L' or 'R'"))
+                .and_then(|&first| Direction::parse_line(first))
+                .and_then(|directions| {
+                    let map = Map::from_lines(lines.get(2..).unwrap_or_default())?;
+                    Ok((directions, map))
+                });
+            parsed.map_err(|e| e.with_day(Self::DAY))
+        }
 
-    #[test]
-    fn part1_example2() {
-        assert_eq!(solve_part1(&test_file("example2.txt")), 6);
-    }
+        fn part1((directions, map): &Self::Input) -> u64 {
+            map.path_length(['A'; 3], ['Z'; 3], directions)
+        }
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve_part1(&test_file("input.txt")), 11567);
-    }
+        fn part2((directions, map): &Self::Input) -> u64 {
+            let mut lengths = Vec::new();
+            for &id in map.nodes().keys() {
+                if id[2] == 'A' {
+                    let mut current = id;
+                    for (steps, &dir) in (0..).zip(directions.iter().cycle()) {
+                        if current[2] == 'Z' {
+                            lengths.push(steps);
+                            break;
+                        }
+                        current = map.step_once(current, dir);
+                    }
+                }
+            }
 
-    #[test]
-    fn part2_example3() {
-        assert_eq!(solve_part2(&test_file("example3.txt")), 6);
+            lcm_of_n(&lengths)
+        }
     }
 
-    #[test]
-    fn part2() {
-        // NOTE: this felt like cheating, LCM isn't guaranteed to work and I couldn't be bothered veryfying the input before running
-        assert_eq!(solve_part2(&test_file("input.txt")), 9858474970153);
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn test_file(name: &str) -> String {
+            example_input(8, name)
+        }
+
+        fn solve_part1(input: &str) -> u64 {
+            Day8::part1(&Day8::parse(input).unwrap())
+        }
+
+        fn solve_part2(input: &str) -> u64 {
+            Day8::part2(&Day8::parse(input).unwrap())
+        }
+
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_part1(&test_file("example1.txt")), 2);
+        }
+
+        #[test]
+        fn part1_example2() {
+            assert_eq!(solve_part1(&test_file("example2.txt")), 6);
+        }
+
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(8, "input.txt") else {
+                return;
+            };
+            assert_answer(8, 1, "input.txt", solve_part1(&input));
+        }
+
+        #[test]
+        fn part2_example3() {
+            assert_eq!(solve_part2(&test_file("example3.txt")), 6);
+        }
+
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(8, "input.txt") else {
+                return;
+            };
+            // NOTE: this felt like cheating, LCM isn't guaranteed to work and I couldn't be bothered veryfying the input before running
+            assert_answer(8, 2, "input.txt", solve_part2(&input));
+        }
     }
 }
 
-mod day9 {
+pub mod day9 {
     use super::*;
-    use history::*;
+    use crate::history::*;
 
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day9", name))
-    }
+    pub struct Day9;
 
-    fn parse_histories(input: &str) -> Vec<ValueHistory> {
-        input
-            .lines()
-            .map(|l| ValueHistory::new(l.split(' ').map(|p| p.parse().unwrap()).collect()))
-            .collect()
-    }
+    impl Solution for Day9 {
+        const DAY: u32 = 9;
 
-    fn solve_part1(input: &str) -> i64 {
-        let histories = parse_histories(input);
+        type Input = Vec<ValueHistory>;
+        type Part1 = i64;
+        type Part2 = i64;
 
-        histories.into_iter().map(|h| h.next_value()).sum()
-    }
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            parse_lines(input).map_err(|e| e.with_day(Self::DAY))
+        }
 
-    fn solve_part2(input: &str) -> i64 {
-        let histories = parse_histories(input);
+        fn part1(histories: &Self::Input) -> i64 {
+            histories.iter().map(|h| h.next_value()).sum()
+        }
 
-        histories.into_iter().map(|h| h.prev_value()).sum()
+        fn part2(histories: &Self::Input) -> i64 {
+            histories.iter().map(|h| h.prev_value()).sum()
+        }
     }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve_part1(&test_file("example1.txt")), 114);
-    }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve_part1(&test_file("input.txt")), 1637452029);
-    }
+        fn test_file(name: &str) -> String {
+            example_input(9, name)
+        }
 
-    #[test]
-    fn part2_example1() {
-        assert_eq!(solve_part2(&test_file("example1.txt")), 2);
-    }
+        fn solve_part1(input: &str) -> i64 {
+            Day9::part1(&Day9::parse(input).unwrap())
+        }
 
-    #[test]
-    fn part2() {
-        assert_eq!(solve_part2(&test_file("input.txt")), 908);
+        fn solve_part2(input: &str) -> i64 {
+            Day9::part2(&Day9::parse(input).unwrap())
+        }
+
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_part1(&test_file("example1.txt")), 114);
+        }
+
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(9, "input.txt") else {
+                return;
+            };
+            assert_answer(9, 1, "input.txt", solve_part1(&input));
+        }
+
+        #[test]
+        fn part2_example1() {
+            assert_eq!(solve_part2(&test_file("example1.txt")), 2);
+        }
+
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(9, "input.txt") else {
+                return;
+            };
+            assert_answer(9, 2, "input.txt", solve_part2(&input));
+        }
     }
 }
 
-#[cfg(test)]
-mod day10 {
+pub mod day10 {
     use super::*;
-    use crate::pipes::{Pipes, Tile};
-    use simple_grid::Grid;
+    use crate::pipes::Pipes;
 
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day10", name))
-    }
+    pub struct Day10;
 
-    fn parse_pipes(input: &str) -> Pipes {
-        let lines: Vec<_> = input.lines().collect();
-        Pipes::new(Grid::new(
-            lines[0].len(),
-            lines.len(),
-            lines
-                .into_iter()
-                .flat_map(|l| l.trim().chars())
-                .map(|c| Tile::try_from(c).expect(&c.to_string()))
-                .collect(),
-        ))
-    }
+    impl Solution for Day10 {
+        const DAY: u32 = 10;
 
-    fn solve_part1(input: &str) -> usize {
-        let pipes = parse_pipes(input);
+        type Input = Pipes;
+        type Part1 = usize;
+        type Part2 = usize;
 
-        let pipe_loop = pipes.travel_loop();
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            input.parse().map_err(|e: ParseError| e.with_day(Self::DAY))
+        }
 
-        pipe_loop.len() / 2
-    }
+        fn part1(pipes: &Pipes) -> usize {
+            let pipe_loop = pipes.travel_loop();
 
-    fn solve_part2(input: &str) -> usize {
-        let pipes = parse_pipes(input);
+            pipe_loop.len() / 2
+        }
 
-        let coverage = pipes.loop_coverage();
+        fn part2(pipes: &Pipes) -> usize {
+            let coverage = pipes.loop_coverage();
 
-        coverage.len()
+            coverage.len()
+        }
     }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve_part1(&test_file("example1.txt")), 8);
-    }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-    #[test]
-    fn part1_example2() {
-        assert_eq!(solve_part1(&test_file("example2.txt")), 4);
-    }
+        fn test_file(name: &str) -> String {
+            example_input(10, name)
+        }
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve_part1(&test_file("input.txt")), 6979);
-    }
+        fn solve_part1(input: &str) -> usize {
+            Day10::part1(&Day10::parse(input).unwrap())
+        }
 
-    #[test]
-    fn part2_example3() {
-        assert_eq!(solve_part2(&test_file("example3.txt")), 10);
-    }
+        fn solve_part2(input: &str) -> usize {
+            Day10::part2(&Day10::parse(input).unwrap())
+        }
 
-    #[test]
-    fn part2_example4() {
-        assert_eq!(solve_part2(&test_file("example4.txt")), 8);
-    }
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_part1(&test_file("example1.txt")), 8);
+        }
 
-    #[test]
-    fn part2_example5() {
-        assert_eq!(solve_part2(&test_file("example5.txt")), 4);
-    }
+        #[test]
+        fn part1_example2() {
+            assert_eq!(solve_part1(&test_file("example2.txt")), 4);
+        }
 
-    #[test]
-    fn part2_example6() {
-        assert_eq!(solve_part2(&test_file("example6.txt")), 4);
-    }
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(10, "input.txt") else {
+                return;
+            };
+            assert_answer(10, 1, "input.txt", solve_part1(&input));
+        }
+
+        #[test]
+        fn part2_example3() {
+            assert_eq!(solve_part2(&test_file("example3.txt")), 10);
+        }
 
-    #[test]
-    fn part2() {
-        assert_eq!(solve_part2(&test_file("input.txt")), 443);
+        #[test]
+        fn part2_example4() {
+            assert_eq!(solve_part2(&test_file("example4.txt")), 8);
+        }
+
+        #[test]
+        fn part2_example5() {
+            assert_eq!(solve_part2(&test_file("example5.txt")), 4);
+        }
+
+        #[test]
+        fn part2_example6() {
+            assert_eq!(solve_part2(&test_file("example6.txt")), 4);
+        }
+
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(10, "input.txt") else {
+                return;
+            };
+            assert_answer(10, 2, "input.txt", solve_part2(&input));
+        }
     }
 }
 
-#[cfg(test)]
-mod day11 {
+pub mod day11 {
     use super::*;
-    use galaxy::*;
-    use simple_grid::Grid;
+    use crate::galaxy::*;
 
-    fn test_file(name: &str) -> String {
-        read_file_contents(&input_data("day11", name))
-    }
+    pub struct Day11;
 
-    fn parse_galaxy_map(input: &str, age: usize) -> GalaxyMap {
-        let lines: Vec<_> = input.lines().collect();
-        GalaxyMap::new(
-            Grid::new(
-                lines[0].len(),
-                lines.len(),
-                lines
-                    .into_iter()
-                    .flat_map(|l| l.chars())
-                    .map(|c| GalaxyOrEmpty::try_from(c).unwrap())
-                    .collect(),
-            ),
-            age,
-        )
-    }
-
-    fn solve(input: &str, age: usize) -> usize {
-        let galaxy_map = parse_galaxy_map(input, age);
+    /// Age used for part 2, where every empty row and column is replaced by a million.
+    pub const PART2_AGE: usize = 999_999;
 
+    pub fn solve(galaxy_map: &GalaxyMap) -> usize {
         let galaxies = galaxy_map.galaxies();
         let mut total_dist = 0;
         for i in 0..galaxies.len() {
             let from = galaxies[i];
-            for j in i + 1..galaxies.len() {
-                let to = galaxies[j];
+            for &to in &galaxies[i + 1..] {
                 let dist = galaxy_map.distance_between(from, to);
                 total_dist += dist;
             }
@@ -969,24 +1223,63 @@ mod day11 {
         total_dist
     }
 
-    #[test]
-    fn part1_example1() {
-        assert_eq!(solve(&test_file("example1.txt"), 1), 374);
-    }
+    impl Solution for Day11 {
+        const DAY: u32 = 11;
 
-    #[test]
-    fn part1() {
-        assert_eq!(solve(&test_file("input.txt"), 1), 9214785);
-    }
+        type Input = GalaxyMap;
+        type Part1 = usize;
+        type Part2 = usize;
 
-    #[test]
-    fn part2_example1() {
-        assert_eq!(solve(&test_file("example1.txt"), 9), 1030);
-        assert_eq!(solve(&test_file("example1.txt"), 99), 8410);
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            input.parse().map_err(|e: ParseError| e.with_day(Self::DAY))
+        }
+
+        fn part1(galaxy_map: &Self::Input) -> usize {
+            solve(galaxy_map)
+        }
+
+        fn part2(galaxy_map: &Self::Input) -> usize {
+            solve(&galaxy_map.with_age(PART2_AGE))
+        }
     }
 
-    #[test]
-    fn part2() {
-        assert_eq!(solve(&test_file("input.txt"), 999_999), 613686987427);
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn test_file(name: &str) -> String {
+            example_input(11, name)
+        }
+
+        fn solve_input(input: &str, age: usize) -> usize {
+            solve(&input.parse::<GalaxyMap>().unwrap().with_age(age))
+        }
+
+        #[test]
+        fn part1_example1() {
+            assert_eq!(solve_input(&test_file("example1.txt"), 1), 374);
+        }
+
+        #[test]
+        fn part1() {
+            let Some(input) = private_input(11, "input.txt") else {
+                return;
+            };
+            assert_answer(11, 1, "input.txt", solve_input(&input, 1));
+        }
+
+        #[test]
+        fn part2_example1() {
+            assert_eq!(solve_input(&test_file("example1.txt"), 9), 1030);
+            assert_eq!(solve_input(&test_file("example1.txt"), 99), 8410);
+        }
+
+        #[test]
+        fn part2() {
+            let Some(input) = private_input(11, "input.txt") else {
+                return;
+            };
+            assert_answer(11, 2, "input.txt", solve_input(&input, PART2_AGE));
+        }
     }
 }
@@ -0,0 +1,670 @@
+use aoc_2023::{
+    answers::{self, Answers, AnswersError, Expected, Status, DEFAULT_ANSWERS_FILE},
+    bag::{Dominance, Draw},
+    bench::{self, BenchConfig, BenchReport, Phase},
+    calibration::CalibrationVocabulary,
+    day2::{self, Day2},
+    day4::Day4,
+    identify,
+    input::InputStore,
+    json::Json,
+    runner::{self, RunnerConfig, Status as RunStatus},
+    scratch::{PrizeOverflow, ScratchcardGame},
+    solution, Solution,
+};
+use std::{
+    env,
+    io::{self, Read},
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+const USAGE: &str = "usage:
+    aoc run --day N [--part P] [--input PATH | -] [--input-dir DIR]
+    aoc run --all [--threads N] [--timeout SECONDS] [--answers PATH] [--input-dir DIR]
+    aoc identify FILE | -
+    aoc explain --day 1 [--part P] [--input PATH | -] [--warnings] [--input-dir DIR]
+    aoc explain --day 4 [--card ID] [--top N] [--depth N] [--dot PATH] [--input PATH | -]
+                [--input-dir DIR]
+    aoc cubes [--input PATH | -] [--without-replacement] [--input-dir DIR]
+    aoc verify [--answers PATH] [--input-dir DIR]
+    aoc record [--day N] [--input NAME] [--plain] [--answers PATH] [--input-dir DIR]
+    aoc bench [--day N] [--input NAME] [--warmup N] [--samples N] [--output PATH]
+              [--baseline PATH] [--threshold PERCENT] [--input-dir DIR]
+
+inputs are looked up as DIR/2023/dayN/input.txt, DIR defaults to $AOC_INPUT_DIR or inputs
+run --all runs every day in parallel, a day that panics or takes longer than --timeout
+(default 60) seconds is reported without stopping the others
+identify guesses the day an input belongs to, run warns if --day looks wrong
+explain lists the tokens found on every line of a calibration document, --warnings
+only shows lines that look suspicious
+explain --day 4 shows which cards won the copies of every scratchcard, --card shows the
+--top (default 5) contributors and a tree --depth (default 1) levels deep, --dot writes a
+Graphviz graph
+cubes groups the day 2 games by minimal bag, frontier bags are marked with *
+known answers are read from answers.toml unless --answers is given
+record adds answers that are not known yet, hashed unless --plain is given
+bench writes JSON to bench_output.json, regressions are medians more than
+--threshold percent (default 10) slower than in the --baseline JSON";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run_command(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            eprintln!("{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_command(args: &[String]) -> Result<(), String> {
+    let (command, rest) = args.split_first().ok_or("missing command")?;
+    if command == "identify" {
+        let [path] = rest else {
+            return Err("identify expects a single FILE".to_string());
+        };
+        return identify(path);
+    }
+    let flags = Flags::parse(rest)?;
+    if let Some(allowed) = allowed_flags(command, &flags) {
+        flags.check(allowed)?;
+    }
+    let store = match flags.value("input-dir") {
+        Some(dir) => InputStore::new(dir),
+        None => InputStore::from_env(),
+    };
+    match command.as_str() {
+        "run" if flags.has("all") => {
+            let mut config = RunnerConfig::new();
+            if let Some(threads) = flags.number("threads")? {
+                config = config.with_threads(threads as usize);
+            }
+            if let Some(timeout) = flags.number("timeout")? {
+                config = config.with_timeout(Duration::from_secs(timeout.into()));
+            }
+            run_all(
+                &store,
+                flags.value("answers").unwrap_or(DEFAULT_ANSWERS_FILE),
+                config,
+            )
+        }
+        "run" => {
+            let day = flags.number("day")?.ok_or("missing --day")?;
+            let part = flags.number("part")?;
+            let input = flags.value("input");
+            run_day(&store, day, part, input)
+        }
+        "explain" => match flags.number("day")?.ok_or("missing --day")? {
+            4 => explain_scratchcards(
+                &store,
+                flags.value("input"),
+                flags.number("card")?,
+                flags.number("top")?.map_or(5, |n| n as usize),
+                flags.number("depth")?.map_or(1, |n| n as usize),
+                flags.value("dot"),
+            ),
+            day => explain(
+                &store,
+                day,
+                flags.number("part")?.unwrap_or(2),
+                flags.value("input"),
+                flags.has("warnings"),
+            ),
+        },
+        "cubes" => {
+            let draw = if flags.has("without-replacement") {
+                Draw::WithoutReplacement
+            } else {
+                Draw::WithReplacement
+            };
+            cubes(&store, flags.value("input"), draw)
+        }
+        "verify" => verify(
+            &store,
+            flags.value("answers").unwrap_or(DEFAULT_ANSWERS_FILE),
+        ),
+        "record" => record(
+            &store,
+            flags.value("answers").unwrap_or(DEFAULT_ANSWERS_FILE),
+            flags.number("day")?,
+            flags.value("input").unwrap_or("input.txt"),
+            !flags.has("plain"),
+        ),
+        "bench" => {
+            let defaults = BenchConfig::default();
+            let config = BenchConfig::new(
+                flags
+                    .number("warmup")?
+                    .map_or(defaults.warmup(), |n| n as usize),
+                flags
+                    .number("samples")?
+                    .map_or(defaults.samples(), |n| n as usize),
+            );
+            let threshold = match flags.value("threshold") {
+                Some(t) => t
+                    .parse::<f64>()
+                    .map_err(|_| format!("--threshold expects a percentage, got '{t}'"))?,
+                None => 10.0,
+            };
+            run_bench(
+                &store,
+                flags.number("day")?,
+                flags.value("input").unwrap_or("input.txt"),
+                config,
+                flags.value("output").unwrap_or("bench_output.json"),
+                flags.value("baseline"),
+                threshold / 100.0,
+            )
+        }
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        other => Err(format!("unknown command '{other}'")),
+    }
+}
+
+/// Flags each form of a command takes, as listed in USAGE. `None` for unknown commands.
+fn allowed_flags(command: &str, flags: &Flags) -> Option<&'static [&'static str]> {
+    let allowed: &[&str] = match command {
+        "run" if flags.has("all") => &["all", "threads", "timeout", "answers", "input-dir"],
+        "run" => &["day", "part", "input", "input-dir"],
+        "explain" if flags.number("day") == Ok(Some(4)) => {
+            &["day", "card", "top", "depth", "dot", "input", "input-dir"]
+        }
+        "explain" => &["day", "part", "input", "warnings", "input-dir"],
+        "cubes" => &["input", "without-replacement", "input-dir"],
+        "verify" => &["answers", "input-dir"],
+        "record" => &["day", "input", "plain", "answers", "input-dir"],
+        "bench" => &[
+            "day",
+            "input",
+            "warmup",
+            "samples",
+            "output",
+            "baseline",
+            "threshold",
+            "input-dir",
+        ],
+        "help" | "--help" | "-h" => &[],
+        _ => return None,
+    };
+
+    Some(allowed)
+}
+
+fn run_day(
+    store: &InputStore,
+    day: u32,
+    part: Option<u32>,
+    input: Option<&str>,
+) -> Result<(), String> {
+    let solution = solution::get(day).ok_or(format!("no solution for day {day}"))?;
+    let parts = match part {
+        Some(p @ (1 | 2)) => vec![p],
+        Some(p) => return Err(format!("no part {p}, expected 1 or 2")),
+        None => vec![1, 2],
+    };
+
+    let input = match input {
+        Some(path) => read_input(path)?,
+        None => store.read(day, "input.txt").map_err(|e| e.to_string())?,
+    };
+    warn_if_other_day(day, &input);
+
+    let (parsed, parse_time) = timed(|| solution.parse(&input));
+    let parsed = parsed.map_err(|e| e.to_string())?;
+    println!("day {day}, parse: {parse_time:.2?}");
+    for part in parts {
+        let (answer, solve_time) = timed(|| parsed.solve(part).unwrap());
+        println!("day {day}, part {part}: {answer} ({solve_time:.2?})");
+    }
+
+    Ok(())
+}
+
+fn run_all(store: &InputStore, answers_path: &str, config: RunnerConfig) -> Result<(), String> {
+    let answers = load_answers(answers_path)?;
+    let reports = runner::run(solution::all(), store, &answers, &config);
+
+    println!(
+        "{:>3} | {:>16} | {:>16} | {:>10} | status",
+        "day", "part 1", "part 2", "time"
+    );
+    let mut failures = 0;
+    for report in &reports {
+        let (part1, part2) = report.answers().unwrap_or_default();
+        println!(
+            "{:>3} | {part1:>16} | {part2:>16} | {:>10} | {}",
+            report.day(),
+            format!("{:.2?}", report.elapsed()),
+            report.status()
+        );
+        if !matches!(
+            report.status(),
+            RunStatus::Ok | RunStatus::Unverified | RunStatus::Skipped
+        ) {
+            failures += 1;
+        }
+    }
+    let total: Duration = reports.iter().map(|r| r.elapsed()).sum();
+    println!("total: {total:.2?}");
+
+    match failures {
+        0 => Ok(()),
+        n => Err(format!("{n} days did not finish with a correct answer")),
+    }
+}
+
+fn verify(store: &InputStore, answers_path: &str) -> Result<(), String> {
+    let answers = Answers::load(answers_path).map_err(|e| e.to_string())?;
+    let (mut passed, mut failed, mut unknown) = (0, 0, 0);
+    for verification in answers::verify(&answers, store) {
+        let answer = verification.answer();
+        let status = match verification.status() {
+            Status::Pass => {
+                passed += 1;
+                "pass".to_string()
+            }
+            Status::Fail { actual } => {
+                failed += 1;
+                match answer.expected() {
+                    Expected::Plain(expected) => format!("FAIL, expected {expected}, got {actual}"),
+                    Expected::Hashed { .. } => format!("FAIL, got {actual}"),
+                }
+            }
+            Status::Unknown { reason } => {
+                unknown += 1;
+                format!("unknown, {reason}")
+            }
+        };
+        println!(
+            "day {:>2}, part {}, {:<14} {status}",
+            answer.day(),
+            answer.part(),
+            answer.input()
+        );
+    }
+    println!("{passed} passed, {failed} failed, {unknown} unknown");
+
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!("{failed} answers did not match")),
+    }
+}
+
+fn record(
+    store: &InputStore,
+    answers_path: &str,
+    day: Option<u32>,
+    name: &str,
+    hash: bool,
+) -> Result<(), String> {
+    let mut answers = load_answers(answers_path)?;
+    let recorded = answers::record(&mut answers, store, day, name, hash)?;
+    for answer in &recorded {
+        println!(
+            "day {:>2}, part {}, {:<14} recorded",
+            answer.day(),
+            answer.part(),
+            answer.input()
+        );
+    }
+    if !recorded.is_empty() {
+        answers.save(answers_path).map_err(|e| e.to_string())?;
+    }
+    println!("{} answers recorded", recorded.len());
+
+    Ok(())
+}
+
+fn run_bench(
+    store: &InputStore,
+    day: Option<u32>,
+    name: &str,
+    config: BenchConfig,
+    output: &str,
+    baseline: Option<&str>,
+    threshold: f64,
+) -> Result<(), String> {
+    // read the baseline first, so a typo doesn't cost a full benchmark run
+    let baseline = baseline
+        .map(|path| {
+            let json: Json = read_input(path)?
+                .parse()
+                .map_err(|e| format!("{path}: {e}"))?;
+            BenchReport::from_json(&json).map_err(|e| format!("{path}: {e}"))
+        })
+        .transpose()?;
+
+    println!(
+        "{:>3} | {:<6} | {:>10} | {:>10} | {:>10}",
+        "day", "phase", "min", "median", "p95"
+    );
+    let mut benches = Vec::new();
+    for solution in solution::all().filter(|s| day.is_none_or(|d| s.day() == d)) {
+        let day = solution.day();
+        let input = match store.read(day, name) {
+            Ok(input) => input,
+            Err(e) if e.is_missing() => continue,
+            Err(e) => return Err(e.to_string()),
+        };
+        let bench = bench::bench_day(solution, name, &input, config).map_err(|e| e.to_string())?;
+        for phase in Phase::ALL {
+            let stats = bench.stats(phase);
+            println!(
+                "{day:>3} | {phase:<6} | {:>10} | {:>10} | {:>10}",
+                format!("{:.2?}", stats.min()),
+                format!("{:.2?}", stats.median()),
+                format!("{:.2?}", stats.p95()),
+            );
+        }
+        benches.push(bench);
+    }
+    if benches.is_empty() {
+        return Err(format!("no inputs named '{name}' to benchmark"));
+    }
+
+    let report = BenchReport::new(benches);
+    std::fs::write(output, format!("{:#}\n", report.to_json()))
+        .map_err(|e| format!("could not write '{output}': {e}"))?;
+    println!("results written to {output}");
+
+    let Some(baseline) = baseline else {
+        return Ok(());
+    };
+    let mut regressions = 0;
+    for comparison in report.compare(&baseline) {
+        if comparison.is_regression(threshold) {
+            regressions += 1;
+            println!(
+                "regression: day {}, {}: {:.2?} -> {:.2?} ({:+.1}%)",
+                comparison.day(),
+                comparison.phase(),
+                comparison.baseline(),
+                comparison.current(),
+                comparison.change() * 100.0
+            );
+        }
+    }
+
+    match regressions {
+        0 => {
+            println!("no regressions against the baseline");
+            Ok(())
+        }
+        n => Err(format!("{n} phases regressed")),
+    }
+}
+
+/// Loads known answers, a missing answers file counts as empty.
+fn load_answers(path: &str) -> Result<Answers, String> {
+    match Answers::load(path) {
+        Ok(answers) => Ok(answers),
+        Err(AnswersError::Io { error, .. }) if error.kind() == io::ErrorKind::NotFound => {
+            Ok(Answers::new())
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Warns when `input` fits another day's grammar better than `day`'s.
+fn warn_if_other_day(day: u32, input: &str) {
+    let matches = identify::identify(input);
+    let Some(best) = matches.first() else {
+        return;
+    };
+    let own = matches
+        .iter()
+        .find(|m| m.day() == day)
+        .map_or(0.0, |m| m.confidence());
+    if best.confidence() > own {
+        eprintln!(
+            "warning: this input looks like day {} ({:.0}% confidence), not day {day} ({:.0}%)",
+            best.day(),
+            best.confidence() * 100.0,
+            own * 100.0
+        );
+    }
+}
+
+fn explain(
+    store: &InputStore,
+    day: u32,
+    part: u32,
+    input: Option<&str>,
+    only_warnings: bool,
+) -> Result<(), String> {
+    if day != 1 {
+        return Err(format!("explain only supports days 1 and 4, not day {day}"));
+    }
+    let vocabulary = match part {
+        1 => CalibrationVocabulary::digits(),
+        2 => CalibrationVocabulary::english(),
+        p => return Err(format!("no part {p}, expected 1 or 2")),
+    };
+    let input = match input {
+        Some(path) => read_input(path)?,
+        None => store.read(day, "input.txt").map_err(|e| e.to_string())?,
+    };
+
+    let mut flagged = 0;
+    let mut missing = 0;
+    for (number, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let explanation = vocabulary.explain(line);
+        if !explanation.warnings().is_empty() {
+            flagged += 1;
+        }
+        if explanation.value().is_none() {
+            missing += 1;
+        }
+        if !only_warnings || !explanation.warnings().is_empty() {
+            print!("line {}: {explanation}", number + 1);
+        }
+    }
+    println!("{flagged} lines with warnings, {missing} without a value");
+
+    Ok(())
+}
+
+fn explain_scratchcards(
+    store: &InputStore,
+    input: Option<&str>,
+    card: Option<u32>,
+    top: usize,
+    depth: usize,
+    dot: Option<&str>,
+) -> Result<(), String> {
+    let input = match input {
+        Some(path) => read_input(path)?,
+        None => store.read(4, "input.txt").map_err(|e| e.to_string())?,
+    };
+    let cards = Day4::parse(&input).map_err(|e| e.to_string())?;
+    let provenance = ScratchcardGame::new(&cards)
+        .and_then(|game| game.provenance(PrizeOverflow::Clamp))
+        .map_err(|e| e.to_string())?;
+
+    if let Some(path) = dot {
+        std::fs::write(path, provenance.to_dot())
+            .map_err(|e| format!("could not write '{path}': {e}"))?;
+        println!("wrote {path}");
+    }
+    match card {
+        Some(id) => {
+            if provenance.card(id).is_none() {
+                return Err(format!("there is no card {id}"));
+            }
+            print!("{}", provenance.tree(id, depth));
+            println!("top contributors:");
+            for (source, copies) in provenance.top_contributors(id, top) {
+                println!("    card {source}: {copies}");
+            }
+        }
+        None => {
+            for (id, card) in provenance.iter() {
+                print!("card {id}: {} copies", card.copies());
+                if let Some((source, copies)) = provenance.top_contributors(id, 1).first() {
+                    print!(", most from card {source} ({copies})");
+                }
+                println!();
+            }
+            println!("{} scratchcards", provenance.total());
+        }
+    }
+
+    Ok(())
+}
+
+fn cubes(store: &InputStore, input: Option<&str>, draw: Draw) -> Result<(), String> {
+    let input = match input {
+        Some(path) => read_input(path)?,
+        None => store.read(2, "input.txt").map_err(|e| e.to_string())?,
+    };
+    println!("{}", cubes_report(&input, draw)?);
+
+    Ok(())
+}
+
+/// The dominance table of the games in `input`, which can use any colours.
+fn cubes_report(input: &str, draw: Draw) -> Result<String, String> {
+    let games = day2::parse_games(input, None).map_err(|e| e.with_day(Day2::DAY).to_string())?;
+
+    Ok(Dominance::new(&games, draw).to_string())
+}
+
+fn identify(path: &str) -> Result<(), String> {
+    let input = read_input(path)?;
+    let matches = identify::identify(&input);
+    let best = matches
+        .first()
+        .ok_or("the input doesn't look like any day")?;
+    println!(
+        "day {} ({:.0}% confidence)",
+        best.day(),
+        best.confidence() * 100.0
+    );
+    for other in &matches[1..] {
+        println!(
+            "    or day {} ({:.0}%)",
+            other.day(),
+            other.confidence() * 100.0
+        );
+    }
+
+    Ok(())
+}
+
+/// Reads a file, or stdin if `path` is `-`.
+fn read_input(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut s = String::new();
+        io::stdin()
+            .read_to_string(&mut s)
+            .map_err(|e| format!("could not read stdin: {e}"))?;
+        return Ok(s);
+    }
+    std::fs::read_to_string(path).map_err(|e| format!("could not read '{path}': {e}"))
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
+}
+
+/// Minimal `--flag [value]` parser, flags without a value are stored as switches.
+struct Flags {
+    pairs: Vec<(String, Option<String>)>,
+}
+
+impl Flags {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut pairs = Vec::new();
+        let mut args = args.iter().peekable();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or(format!("unexpected argument '{arg}'"))?;
+            let value = args
+                .next_if(|v| !v.starts_with("--"))
+                .map(|v| v.to_string());
+            pairs.push((name.to_string(), value));
+        }
+
+        Ok(Self { pairs })
+    }
+
+    /// Fails on the first flag that isn't in `allowed`.
+    fn check(&self, allowed: &[&str]) -> Result<(), String> {
+        match self
+            .pairs
+            .iter()
+            .find(|(n, _)| !allowed.contains(&n.as_str()))
+        {
+            Some((name, _)) => Err(format!("unknown flag '--{name}'")),
+            None => Ok(()),
+        }
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.pairs.iter().any(|(n, _)| n == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, v)| v.as_deref())
+    }
+
+    fn number(&self, name: &str) -> Result<Option<u32>, String> {
+        self.value(name)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| format!("--{name} expects a number, got '{v}'"))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn flags_test() {
+        let error = run_command(&args("run --dya 3")).unwrap_err();
+        assert_eq!(error, "unknown flag '--dya'");
+        let error = run_command(&args("explain --day 4 --warnings")).unwrap_err();
+        assert_eq!(error, "unknown flag '--warnings'");
+        let error = run_command(&args("run --all --day 3")).unwrap_err();
+        assert_eq!(error, "unknown flag '--day'");
+        let error = run_command(&args("frobnicate --x")).unwrap_err();
+        assert_eq!(error, "unknown command 'frobnicate'");
+
+        let flags = Flags::parse(&args("--day 1 --warnings --input -")).unwrap();
+        let allowed = allowed_flags("explain", &flags).unwrap();
+        assert_eq!(flags.check(allowed), Ok(()));
+        assert_eq!(flags.value("input"), Some("-"));
+    }
+
+    #[test]
+    fn cubes_report_test() {
+        let input = "Game 1: 3 purple, 1 red; 2 purple\nGame 2: 1 purple";
+        let report = cubes_report(input, Draw::WithReplacement).unwrap();
+        assert!(report.contains("3 purple"), "{report}");
+        assert!(report.contains("1 purple"), "{report}");
+
+        let error = cubes_report("Game 1: 3", Draw::WithReplacement).unwrap_err();
+        assert!(error.starts_with("day 2, line 1"), "{error}");
+    }
+}
@@ -1,7 +1,12 @@
-use std::collections::HashMap;
+use crate::{
+    error::ParseError,
+    parse::{input_lines, InputLine},
+};
+use std::{collections::HashMap, fmt::Display, str::FromStr};
 
 type Id = [char; 3];
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Map {
     nodes: HashMap<Id, Node>,
 }
@@ -41,11 +46,119 @@ impl Map {
     }
 }
 
-#[derive(Clone, Copy)]
+impl Map {
+    /// Parses `AAA = (BBB, CCC)` lines, keeping their line numbers for errors.
+    pub(crate) fn from_lines(lines: &[InputLine]) -> Result<Self, ParseError> {
+        let mut nodes = HashMap::new();
+
+        for &line in lines {
+            let (id, lr) = line.split_once(line.text(), " = ")?;
+            let lr = line.strip_prefix(lr, "(")?;
+            let lr = lr
+                .strip_suffix(')')
+                .ok_or_else(|| line.error(&lr[lr.len()..], "')'"))?;
+            let (left, right) = line.split_once(lr, ", ")?;
+
+            nodes.insert(
+                parse_id(line, id)?,
+                Node(parse_id(line, left)?, parse_id(line, right)?),
+            );
+        }
+
+        Ok(Self::new(nodes))
+    }
+}
+
+fn parse_id(line: InputLine, id: &str) -> Result<Id, ParseError> {
+    let mut chars = id.chars();
+    match (chars.next(), chars.next(), chars.next(), chars.next()) {
+        (Some(a), Some(b), Some(c), None) => Ok([a, b, c]),
+        _ => Err(line.error(id, "a node id of three characters")),
+    }
+}
+
+impl FromStr for Map {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<_> = input_lines(s).collect();
+        Self::from_lines(&lines)
+    }
+}
+
+/// Writes one node per line, sorted by id.
+impl Display for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ids: Vec<_> = self.nodes.keys().collect();
+        ids.sort();
+        for (i, id) in ids.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let Node(left, right) = self.nodes[id];
+            let [id, left, right] = [id, &left, &right].map(|id| id.iter().collect::<String>());
+            write!(f, "{id} = ({left}, {right})")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
     Left,
     Right,
 }
 
-#[derive(Clone, Copy, Debug)]
+impl Direction {
+    /// Parses a line of `L` and `R` instructions.
+    pub fn parse_line(line: InputLine) -> Result<Vec<Self>, ParseError> {
+        let text = line.text();
+        text.char_indices()
+            .map(|(i, c)| Self::try_from(c).map_err(|e| line.locate(&text[i..], e)))
+            .collect()
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = ParseError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            'L' => Ok(Self::Left),
+            'R' => Ok(Self::Right),
+            _ => Err(ParseError::new(value, "'L' or 'R'")),
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Left => write!(f, "L"),
+            Direction::Right => write!(f, "R"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Node(pub Id, pub Id);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_test() {
+        let input = "AAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)";
+        let map: Map = input.parse().unwrap();
+        assert_eq!(map.nodes()[&['B'; 3]], Node(['A'; 3], ['Z'; 3]));
+        assert_eq!(map.to_string(), input);
+        assert_eq!(map.to_string().parse::<Map>(), Ok(map));
+
+        let error = "AAA = (BBB, BBB)\nBBB = (AAA ZZZ)"
+            .parse::<Map>()
+            .unwrap_err();
+        assert_eq!((error.line(), error.column()), (Some(2), Some(8)));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::error::ParseError;
+use simple_grid::Grid;
+use std::{fmt::Display, str::FromStr};
+
+/// A single line of puzzle input, remembers where it came from so errors can point at it.
+#[derive(Debug, Clone, Copy)]
+pub struct InputLine<'a> {
+    number: usize,
+    text: &'a str,
+}
+
+impl<'a> InputLine<'a> {
+    pub fn new(number: usize, text: &'a str) -> Self {
+        Self { number, text }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Builds an error pointing at `at`, which should be a slice of this line.
+    /// Anything else is reported at the end of the line.
+    pub fn error(&self, at: &str, expected: &str) -> ParseError {
+        ParseError::new(at, expected).at(self.number, self.column_of(at))
+    }
+
+    /// Attaches the position of this line to an error produced by a lower level parser.
+    pub fn locate(&self, at: &str, error: ParseError) -> ParseError {
+        error.at(self.number, self.column_of(at))
+    }
+
+    pub fn parse<T: FromStr>(&self, at: &str, expected: &str) -> Result<T, ParseError> {
+        at.parse().map_err(|_| self.error(at, expected))
+    }
+
+    pub fn strip_prefix(&self, at: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+        at.strip_prefix(prefix)
+            .ok_or_else(|| self.error(at, &format!("'{prefix}'")))
+    }
+
+    pub fn split_once(
+        &self,
+        at: &'a str,
+        delimiter: &str,
+    ) -> Result<(&'a str, &'a str), ParseError> {
+        at.split_once(delimiter)
+            .ok_or_else(|| self.error(at, &format!("'{delimiter}'")))
+    }
+
+    fn column_of(&self, at: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let offset = (at.as_ptr() as usize).wrapping_sub(start);
+        if offset <= self.text.len() {
+            offset + 1
+        } else {
+            self.text.len() + 1
+        }
+    }
+}
+
+/// Splits `input` into numbered lines.
+pub fn input_lines(input: &str) -> impl Iterator<Item = InputLine<'_>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, text)| InputLine::new(i + 1, text))
+}
+
+/// Parses every non-empty line of `input` on its own.
+pub fn parse_lines<T>(input: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr<Err = ParseError>,
+{
+    input_lines(input)
+        .filter(|l| !l.text().trim().is_empty())
+        .map(|l| {
+            l.text()
+                .parse()
+                .map_err(|e: ParseError| e.offset_lines(l.number() - 1))
+        })
+        .collect()
+}
+
+/// Parses a rectangular grid of characters, one row per line. Widths count characters,
+/// not bytes.
+pub fn parse_grid<T>(
+    input: &str,
+    tile: impl Fn(char) -> Result<T, ParseError>,
+) -> Result<(usize, usize, Vec<T>), ParseError> {
+    let lines: Vec<_> = input_lines(input).collect();
+    let first = lines
+        .first()
+        .ok_or_else(|| ParseError::missing_line(1, "at least one row"))?;
+    let width = first.text().chars().count();
+    let mut tiles = Vec::with_capacity(width * lines.len());
+    for line in &lines {
+        if line.text().chars().count() != width {
+            return Err(line.error(line.text(), &format!("a row of {width} characters")));
+        }
+        for (i, c) in line.text().char_indices() {
+            let t = tile(c).map_err(|e| line.locate(&line.text()[i..], e))?;
+            tiles.push(t);
+        }
+    }
+
+    Ok((width, lines.len(), tiles))
+}
+
+/// Writes `grid` one row per line, the inverse of [`parse_grid`].
+pub fn write_grid<T: Display>(grid: &Grid<T>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for row in grid.rows() {
+        if row > 0 {
+            writeln!(f)?;
+        }
+        for tile in grid.row_iter(row) {
+            write!(f, "{tile}")?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_line_error_test() {
+        let line = InputLine::new(3, "Game 7: 3 purple");
+        let (_, grab) = line.split_once(line.text(), ": ").unwrap();
+        let (_, color) = line.split_once(grab, " ").unwrap();
+        let error = line.error(color, "a colour");
+
+        assert_eq!(error.line(), Some(3));
+        assert_eq!(error.column(), Some(11));
+        assert_eq!(error.text(), "purple");
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 11: expected a colour, found 'purple'"
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Number(u32);
+
+    impl FromStr for Number {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            InputLine::new(1, s).parse(s, "a number").map(Number)
+        }
+    }
+
+    #[test]
+    fn parse_lines_test() {
+        let numbers: Vec<Number> = parse_lines("1\n\n3").unwrap();
+        assert_eq!(numbers, vec![Number(1), Number(3)]);
+
+        let error = parse_lines::<Number>("1\n\nx").unwrap_err();
+        assert_eq!((error.line(), error.column()), (Some(3), Some(1)));
+    }
+
+    #[test]
+    fn parse_grid_test() {
+        let tile = |c: char| match c {
+            '.' => Ok(false),
+            '#' => Ok(true),
+            _ => Err(ParseError::new(c, "'.' or '#'")),
+        };
+        assert_eq!(
+            parse_grid(".#\n#.", tile),
+            Ok((2, 2, vec![false, true, true, false]))
+        );
+
+        let error = parse_grid(".#\n#?", tile).unwrap_err();
+        assert_eq!((error.line(), error.column()), (Some(2), Some(2)));
+
+        let error = parse_grid(".#\n#..", tile).unwrap_err();
+        assert_eq!(error.expected(), "a row of 2 characters");
+
+        // columns count bytes, so é takes two
+        let error = parse_grid(".é\n#.", tile).unwrap_err();
+        assert_eq!(
+            (error.line(), error.column(), error.text()),
+            (Some(1), Some(2), "é")
+        );
+        assert_eq!(
+            parse_grid("é\n.", Ok::<char, ParseError>),
+            Ok((1, 2, vec!['é', '.']))
+        );
+        let error = parse_grid("..\né", Ok::<char, ParseError>).unwrap_err();
+        assert_eq!(error.line(), Some(2));
+    }
+}
@@ -1,9 +1,15 @@
+use crate::{
+    error::ParseError,
+    parse::{parse_grid, write_grid},
+};
 use simple_grid::{Grid, GridIndex};
 use std::{
     collections::{HashSet, VecDeque},
     fmt::Display,
+    str::FromStr,
 };
 
+#[derive(Debug, PartialEq, Eq)]
 pub struct Pipes {
     pipes: Grid<Tile>,
     start: GridIndex,
@@ -278,6 +284,25 @@ impl Pipes {
     }
 }
 
+impl FromStr for Pipes {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (w, h, tiles) = parse_grid(s, Tile::try_from)?;
+        if !tiles.iter().any(|t| matches!(t, Tile::Start)) {
+            return Err(ParseError::missing_line(h + 1, "a start tile 'S'"));
+        }
+
+        Ok(Self::new(Grid::new(w, h, tiles)))
+    }
+}
+
+impl Display for Pipes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_grid(&self.pipes, f)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     North,
@@ -307,7 +332,7 @@ fn nesw() -> impl Iterator<Item = Direction> {
     .into_iter()
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tile {
     Vert,
     Hori,
@@ -349,7 +374,7 @@ impl Display for Tile {
 }
 
 impl TryFrom<char> for Tile {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
         Ok(match value {
@@ -361,7 +386,7 @@ impl TryFrom<char> for Tile {
             'F' => Self::SE,
             '.' => Self::Ground,
             'S' => Self::Start,
-            _ => return Err(()),
+            _ => return Err(ParseError::new(value, "a pipe tile")),
         })
     }
 }
@@ -389,6 +414,17 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn round_trip_test() {
+        let input = "..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ...";
+        let pipes: Pipes = input.parse().unwrap();
+        assert_eq!(pipes.to_string(), input);
+        assert_eq!(pipes.to_string().parse::<Pipes>(), Ok(pipes));
+
+        let error = "..F7.\n.FJ|.".parse::<Pipes>().unwrap_err();
+        assert_eq!(error.line(), Some(3));
+    }
+
     #[test]
     fn is_clockwise_test() {
         let pipes = example_pipes();
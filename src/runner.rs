@@ -0,0 +1,395 @@
+use crate::{answers::Answers, input::InputStore, solution::DynSolution};
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::Display,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunnerConfig {
+    threads: usize,
+    timeout: Duration,
+    input: String,
+}
+
+impl RunnerConfig {
+    pub fn new() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(4, |n| n.get()),
+            timeout: Duration::from_secs(60),
+            input: "input.txt".to_string(),
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Wall-clock limit for parsing and solving both parts of a single day.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_input(mut self, input: impl Into<String>) -> Self {
+        self.input = input.into();
+        self
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+}
+
+impl Default for RunnerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// Both parts match the known answers.
+    Ok,
+    /// Solved, but there are no known answers to compare with.
+    Unverified,
+    Wrong {
+        parts: Vec<u32>,
+    },
+    Panicked {
+        message: String,
+    },
+    TimedOut,
+    Skipped,
+    /// The input could not be read or parsed.
+    Failed {
+        message: String,
+    },
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Ok => write!(f, "ok"),
+            Status::Unverified => write!(f, "ok, unverified"),
+            Status::Wrong { parts } => {
+                let parts: Vec<_> = parts.iter().map(|p| p.to_string()).collect();
+                write!(f, "wrong, part {}", parts.join(" and "))
+            }
+            Status::Panicked { message } => write!(f, "panicked: {message}"),
+            Status::TimedOut => write!(f, "timed out"),
+            Status::Skipped => write!(f, "skipped, no input"),
+            Status::Failed { message } => write!(f, "failed: {message}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayReport {
+    day: u32,
+    status: Status,
+    answers: Option<(String, String)>,
+    elapsed: Duration,
+}
+
+impl DayReport {
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    /// Answers to both parts, if the day finished.
+    pub fn answers(&self) -> Option<(&str, &str)> {
+        self.answers
+            .as_ref()
+            .map(|(part1, part2)| (part1.as_str(), part2.as_str()))
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+enum Event {
+    Started { day: u32, at: Instant },
+    Finished(DayReport),
+}
+
+/// Shared state of the worker threads.
+struct Pool {
+    queue: Mutex<VecDeque<&'static dyn DynSolution>>,
+    store: InputStore,
+    answers: Answers,
+    input: String,
+}
+
+impl Pool {
+    fn spawn_worker(self: &Arc<Self>, events: Sender<Event>) {
+        let pool = Arc::clone(self);
+        thread::spawn(move || loop {
+            let Some(solution) = pool.queue.lock().unwrap().pop_front() else {
+                return;
+            };
+            let day = solution.day();
+            let at = Instant::now();
+            if events.send(Event::Started { day, at }).is_err() {
+                return;
+            }
+            let status = panic::catch_unwind(AssertUnwindSafe(|| pool.run(solution)));
+            let (status, answers) = status.unwrap_or_else(|payload| {
+                let message = panic_message(payload.as_ref());
+                (Status::Panicked { message }, None)
+            });
+            let report = DayReport {
+                day,
+                status,
+                answers,
+                elapsed: at.elapsed(),
+            };
+            if events.send(Event::Finished(report)).is_err() {
+                return;
+            }
+        });
+    }
+
+    fn run(&self, solution: &dyn DynSolution) -> (Status, Option<(String, String)>) {
+        let day = solution.day();
+        let input = match self.store.read(day, &self.input) {
+            Ok(input) => input,
+            Err(e) if e.is_missing() => return (Status::Skipped, None),
+            Err(e) => {
+                return (
+                    Status::Failed {
+                        message: e.to_string(),
+                    },
+                    None,
+                )
+            }
+        };
+        let parsed = match solution.parse(&input) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                return (
+                    Status::Failed {
+                        message: e.to_string(),
+                    },
+                    None,
+                )
+            }
+        };
+        let answers = (parsed.part1(), parsed.part2());
+
+        let expected = [1, 2].map(|part| self.answers.get(day, part, &self.input));
+        let wrong: Vec<_> = [(1, &answers.0), (2, &answers.1)]
+            .into_iter()
+            .zip(expected)
+            .filter(|((_, actual), expected)| {
+                expected.is_some_and(|e| !e.expected().matches(actual))
+            })
+            .map(|((part, _), _)| part)
+            .collect();
+        let status = if !wrong.is_empty() {
+            Status::Wrong { parts: wrong }
+        } else if expected.iter().all(Option::is_some) {
+            Status::Ok
+        } else {
+            Status::Unverified
+        };
+
+        (status, Some(answers))
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Runs `solutions` on a pool of worker threads and returns a report per day, ordered by day.
+///
+/// Panics are caught per day. A day that runs past the timeout is reported as timed out and
+/// its worker is abandoned, since threads can't be killed, and replaced by a fresh one.
+pub fn run(
+    solutions: impl IntoIterator<Item = &'static dyn DynSolution>,
+    store: &InputStore,
+    answers: &Answers,
+    config: &RunnerConfig,
+) -> Vec<DayReport> {
+    let queue: VecDeque<_> = solutions.into_iter().collect();
+    let total = queue.len();
+    let pool = Arc::new(Pool {
+        queue: Mutex::new(queue),
+        store: store.clone(),
+        answers: answers.clone(),
+        input: config.input.clone(),
+    });
+    let (events, received) = mpsc::channel();
+    for _ in 0..config.threads.min(total) {
+        pool.spawn_worker(events.clone());
+    }
+
+    let mut running: HashMap<u32, Instant> = HashMap::new();
+    let mut reports = BTreeMap::new();
+    while reports.len() < total {
+        let deadline = running.values().min().map(|&start| start + config.timeout);
+        let event = match deadline {
+            Some(deadline) => {
+                received.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => received.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match event {
+            Ok(Event::Started { day, at }) => {
+                running.insert(day, at);
+            }
+            Ok(Event::Finished(report)) => {
+                // a day that already timed out stays timed out
+                if running.remove(&report.day).is_some() {
+                    reports.insert(report.day, report);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let now = Instant::now();
+                let timed_out: Vec<_> = running
+                    .iter()
+                    .filter(|(_, &start)| now.duration_since(start) >= config.timeout)
+                    .map(|(&day, _)| day)
+                    .collect();
+                for day in timed_out {
+                    let start = running.remove(&day).expect("day is running");
+                    reports.insert(
+                        day,
+                        DayReport {
+                            day,
+                            status: Status::TimedOut,
+                            answers: None,
+                            elapsed: now.duration_since(start),
+                        },
+                    );
+                    pool.spawn_worker(events.clone());
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    reports.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::ParseError, solution, Solution};
+
+    struct Panics;
+
+    impl Solution for Panics {
+        const DAY: u32 = 101;
+        type Input = ();
+        type Part1 = u32;
+        type Part2 = u32;
+
+        fn parse(_: &str) -> Result<(), ParseError> {
+            Ok(())
+        }
+
+        fn part1(_: &()) -> u32 {
+            panic!("no start tile")
+        }
+
+        fn part2(_: &()) -> u32 {
+            0
+        }
+    }
+
+    struct Hangs;
+
+    impl Solution for Hangs {
+        const DAY: u32 = 102;
+        type Input = ();
+        type Part1 = u32;
+        type Part2 = u32;
+
+        fn parse(_: &str) -> Result<(), ParseError> {
+            Ok(())
+        }
+
+        fn part1(_: &()) -> u32 {
+            thread::sleep(Duration::from_secs(10));
+            0
+        }
+
+        fn part2(_: &()) -> u32 {
+            0
+        }
+    }
+
+    #[test]
+    fn run_test() {
+        let root = std::env::temp_dir().join(format!("aoc-runner-test-{}", std::process::id()));
+        for day in [Panics::DAY, Hangs::DAY] {
+            let dir = root.join(format!("day{day}"));
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("example1.txt"), "").unwrap();
+        }
+        let store = InputStore::new("inputs").with_root(&root);
+        let answers: Answers = "[day9.part1]\n\"example1.txt\" = 114\n\n\
+            [day9.part2]\n\"example1.txt\" = 2\n\n\
+            [day6.part1]\n\"example1.txt\" = 1"
+            .parse()
+            .unwrap();
+        let config = RunnerConfig::new()
+            .with_threads(2)
+            .with_timeout(Duration::from_millis(500))
+            .with_input("example1.txt");
+        let solutions: [&'static dyn DynSolution; 4] = [
+            &Hangs,
+            &Panics,
+            solution::get(9).unwrap(),
+            solution::get(6).unwrap(),
+        ];
+
+        let reports = run(solutions, &store, &answers, &config);
+        std::fs::remove_dir_all(&root).unwrap();
+        let statuses: Vec<_> = reports.iter().map(|r| (r.day(), r.status())).collect();
+        assert_eq!(
+            statuses,
+            [
+                (6, &Status::Wrong { parts: vec![1] }),
+                (9, &Status::Ok),
+                (
+                    101,
+                    &Status::Panicked {
+                        message: "no start tile".to_string()
+                    }
+                ),
+                (102, &Status::TimedOut),
+            ]
+        );
+        assert_eq!(reports[1].answers(), Some(("114", "2")));
+    }
+}
@@ -0,0 +1,236 @@
+//! Aho-Corasick automaton for finding the first and last of a set of byte patterns.
+
+use std::collections::VecDeque;
+
+/// A pattern found in a haystack, `start..end` are byte offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pattern: usize,
+    start: usize,
+    end: usize,
+}
+
+impl Match {
+    /// Index of the pattern, in the order it was given to [`Scanner::new`].
+    pub fn pattern(&self) -> usize {
+        self.pattern
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Moves the match right by `offset`, for matches found in a suffix of the haystack.
+    pub fn offset(self, offset: usize) -> Self {
+        Self {
+            start: self.start + offset,
+            end: self.end + offset,
+            ..self
+        }
+    }
+}
+
+/// Deterministic automaton, every state has a transition for every byte.
+#[derive(Debug, Clone)]
+struct Automaton {
+    transitions: Vec<[u32; 256]>,
+    /// Patterns ending in each state, including those reached through failure links.
+    outputs: Vec<Vec<usize>>,
+}
+
+impl Automaton {
+    fn new<'a>(patterns: impl Iterator<Item = &'a [u8]>) -> Self {
+        let mut transitions = vec![[0u32; 256]];
+        let mut outputs = vec![Vec::new()];
+        let mut has_edge = vec![[false; 256]];
+        for (i, pattern) in patterns.enumerate() {
+            let mut state = 0;
+            for &byte in pattern {
+                if !has_edge[state][byte as usize] {
+                    has_edge[state][byte as usize] = true;
+                    transitions[state][byte as usize] = transitions.len() as u32;
+                    transitions.push([0; 256]);
+                    outputs.push(Vec::new());
+                    has_edge.push([false; 256]);
+                }
+                state = transitions[state][byte as usize] as usize;
+            }
+            outputs[state].push(i);
+        }
+
+        // breadth first, so the failure state is always finished before it is needed
+        let mut fail = vec![0; transitions.len()];
+        let mut queue: VecDeque<_> = (0..256)
+            .filter(|&b| has_edge[0][b])
+            .map(|b| transitions[0][b] as usize)
+            .collect();
+        while let Some(state) = queue.pop_front() {
+            for byte in 0..256 {
+                let next = transitions[state][byte] as usize;
+                if has_edge[state][byte] {
+                    fail[next] = transitions[fail[state]][byte] as usize;
+                    let inherited = outputs[fail[next]].clone();
+                    outputs[next].extend(inherited);
+                    queue.push_back(next);
+                } else {
+                    transitions[state][byte] = transitions[fail[state]][byte];
+                }
+            }
+        }
+
+        Self {
+            transitions,
+            outputs,
+        }
+    }
+
+    fn next(&self, state: usize, byte: u8) -> usize {
+        self.transitions[state][byte as usize] as usize
+    }
+}
+
+/// Finds a fixed set of non-empty patterns, overlapping matches included.
+///
+/// `first` and `last` make a single pass from either end and stop as soon as the answer
+/// is known, neither allocates.
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    forward: Automaton,
+    /// Built from the reversed patterns, to scan from the end.
+    backward: Automaton,
+    lengths: Vec<usize>,
+    max_len: usize,
+}
+
+impl Scanner {
+    /// Panics if a pattern is empty.
+    pub fn new<P: AsRef<[u8]>>(patterns: impl IntoIterator<Item = P>) -> Self {
+        let patterns: Vec<P> = patterns.into_iter().collect();
+        let lengths: Vec<_> = patterns.iter().map(|p| p.as_ref().len()).collect();
+        assert!(lengths.iter().all(|&l| l > 0), "patterns can't be empty");
+        let reversed: Vec<Vec<u8>> = patterns
+            .iter()
+            .map(|p| p.as_ref().iter().rev().copied().collect())
+            .collect();
+
+        Self {
+            forward: Automaton::new(patterns.iter().map(|p| p.as_ref())),
+            backward: Automaton::new(reversed.iter().map(|p| p.as_slice())),
+            max_len: lengths.iter().copied().max().unwrap_or(0),
+            lengths,
+        }
+    }
+
+    pub fn pattern_count(&self) -> usize {
+        self.lengths.len()
+    }
+
+    /// The match that starts first, the longest one if several start at the same offset.
+    pub fn first(&self, haystack: &[u8]) -> Option<Match> {
+        let mut best: Option<Match> = None;
+        let mut state = 0;
+        for (i, &byte) in haystack.iter().enumerate() {
+            // a match ending here or later can't start before `best`
+            if best.is_some_and(|b| i >= b.start + self.max_len) {
+                break;
+            }
+            state = self.forward.next(state, byte);
+            for &pattern in &self.forward.outputs[state] {
+                let found = Match {
+                    pattern,
+                    start: i + 1 - self.lengths[pattern],
+                    end: i + 1,
+                };
+                if best.is_none_or(|b| (found.start, b.end) < (b.start, found.end)) {
+                    best = Some(found);
+                }
+            }
+        }
+
+        best
+    }
+
+    /// The match that starts last, the longest one if several start at the same offset.
+    pub fn last(&self, haystack: &[u8]) -> Option<Match> {
+        let mut state = 0;
+        for (i, &byte) in haystack.iter().enumerate().rev() {
+            state = self.backward.next(state, byte);
+            // every match found at this point starts at `i`
+            let longest = self.backward.outputs[state]
+                .iter()
+                .max_by_key(|&&pattern| self.lengths[pattern]);
+            if let Some(&pattern) = longest {
+                return Some(Match {
+                    pattern,
+                    start: i,
+                    end: i + self.lengths[pattern],
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Every match, overlapping ones included, ordered by where they end.
+    pub fn find_overlapping<'a>(&'a self, haystack: &'a [u8]) -> impl Iterator<Item = Match> + 'a {
+        haystack
+            .iter()
+            .enumerate()
+            .scan(0, |state, (i, &byte)| {
+                *state = self.forward.next(*state, byte);
+                Some((i, *state))
+            })
+            .flat_map(move |(i, state)| {
+                self.forward.outputs[state]
+                    .iter()
+                    .map(move |&pattern| Match {
+                        pattern,
+                        start: i + 1 - self.lengths[pattern],
+                        end: i + 1,
+                    })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(matches: impl Iterator<Item = Match>) -> Vec<(usize, usize, usize)> {
+        matches.map(|m| (m.pattern(), m.start(), m.end())).collect()
+    }
+
+    #[test]
+    fn first_and_last_test() {
+        let scanner = Scanner::new(["one", "two", "eight", "1", "2"]);
+        let line = b"xeightwothree1";
+        assert_eq!(scanner.first(line).map(|m| m.pattern()), Some(2));
+        assert_eq!(scanner.last(line).map(|m| m.pattern()), Some(3));
+        assert_eq!(scanner.last(b"oneightwo").map(|m| m.pattern()), Some(1));
+        assert_eq!(scanner.first(b"xyz"), None);
+        assert_eq!(scanner.last(b""), None);
+    }
+
+    #[test]
+    fn earliest_start_test() {
+        // "bc" is found first, but "abcd" starts earlier
+        let scanner = Scanner::new(["abcd", "bc", "ab"]);
+        let first = scanner.first(b"xabcd").unwrap();
+        assert_eq!((first.pattern(), first.start(), first.end()), (0, 1, 5));
+        let last = scanner.last(b"xabcd").unwrap();
+        assert_eq!((last.pattern(), last.start()), (1, 2));
+    }
+
+    #[test]
+    fn find_overlapping_test() {
+        let scanner = Scanner::new(["he", "she", "his", "hers"]);
+        assert_eq!(
+            spans(scanner.find_overlapping(b"ushers")),
+            [(1, 1, 4), (0, 2, 4), (3, 2, 6)]
+        );
+    }
+}
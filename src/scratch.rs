@@ -1,43 +1,712 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use crate::{error::ParseError, parse::InputLine};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::Display,
+    str::FromStr,
+};
 
-#[derive(Clone)]
+/// Set of numbers, a bitset for those below 128 and a sorted list for the rest.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct NumberSet {
+    small: [u64; 2],
+    large: Vec<u32>,
+}
+
+impl NumberSet {
+    const SMALL: u32 = 128;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns false if `n` was already in the set.
+    pub fn insert(&mut self, n: u32) -> bool {
+        if n < Self::SMALL {
+            let (word, bit) = ((n / 64) as usize, 1 << (n % 64));
+            let added = self.small[word] & bit == 0;
+            self.small[word] |= bit;
+            return added;
+        }
+        match self.large.binary_search(&n) {
+            Ok(_) => false,
+            Err(i) => {
+                self.large.insert(i, n);
+                true
+            }
+        }
+    }
+
+    pub fn contains(&self, n: u32) -> bool {
+        if n < Self::SMALL {
+            self.small[(n / 64) as usize] & (1 << (n % 64)) != 0
+        } else {
+            self.large.binary_search(&n).is_ok()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.small
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum::<usize>()
+            + self.large.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.small == [0; 2] && self.large.is_empty()
+    }
+
+    /// In ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        Self::bits(self.small).chain(self.large.iter().copied())
+    }
+
+    /// Numbers in both sets, in ascending order.
+    pub fn intersection<'a>(&'a self, other: &'a NumberSet) -> impl Iterator<Item = u32> + 'a {
+        let small = [
+            self.small[0] & other.small[0],
+            self.small[1] & other.small[1],
+        ];
+        let large = self.large.iter().copied().filter(|&n| other.contains(n));
+        Self::bits(small).chain(large)
+    }
+
+    /// Number of numbers in both sets, without going through them one by one.
+    pub fn intersection_len(&self, other: &NumberSet) -> usize {
+        let small = (self.small[0] & other.small[0]).count_ones()
+            + (self.small[1] & other.small[1]).count_ones();
+        let large = if self.large.is_empty() || other.large.is_empty() {
+            0
+        } else {
+            self.large.iter().filter(|&&n| other.contains(n)).count()
+        };
+        small as usize + large
+    }
+
+    fn bits(words: [u64; 2]) -> impl Iterator<Item = u32> {
+        words.into_iter().enumerate().flat_map(|(i, mut word)| {
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros();
+                word &= word - 1;
+                Some(i as u32 * 64 + bit)
+            })
+        })
+    }
+}
+
+impl FromIterator<u32> for NumberSet {
+    fn from_iter<T: IntoIterator<Item = u32>>(iter: T) -> Self {
+        let mut set = NumberSet::new();
+        for n in iter {
+            set.insert(n);
+        }
+        set
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Card {
     id: u32,
-    winning: HashSet<u32>,
-    hand: HashSet<u32>,
+    winning: NumberSet,
+    hand: NumberSet,
 }
 
 impl Card {
-    pub fn new(id: u32, winning: HashSet<u32>, hand: HashSet<u32>) -> Self {
-        Self { id, winning, hand }
+    pub fn new(
+        id: u32,
+        winning: impl IntoIterator<Item = u32>,
+        hand: impl IntoIterator<Item = u32>,
+    ) -> Self {
+        Self {
+            id,
+            winning: winning.into_iter().collect(),
+            hand: hand.into_iter().collect(),
+        }
     }
 
-    pub fn winning_numbers(&self) -> impl Iterator<Item = u32> + '_ {
-        self.winning.intersection(&self.hand).copied()
+    pub fn winning(&self) -> &NumberSet {
+        &self.winning
     }
 
-    fn won_cards(&self) -> Vec<u32> {
-        (self.id + 1..=self.id + self.winning_numbers().count() as u32).collect()
+    pub fn hand(&self) -> &NumberSet {
+        &self.hand
     }
 
-    pub fn total_scratchcards(cards: &[Card]) -> u64 {
-        let mut card_counts = vec![1; cards.len()];
+    /// Numbers in the hand that are winning numbers, in ascending order.
+    pub fn winning_numbers(&self) -> impl Iterator<Item = u32> + '_ {
+        self.winning.intersection(&self.hand)
+    }
 
-        let mut total_cards = 0_u64;
-        for i in 0..cards.len() {
-            let card = &cards[i];
-            let count = card_counts[i];
-            total_cards += count;
-            let won_cards = (i + 1..i + 1 + card.won_cards().len());
-            for j in won_cards {
-                card_counts[j] += count;
-            }
+    pub fn match_count(&self) -> usize {
+        self.winning.intersection_len(&self.hand)
+    }
+
+    /// 1 point for the first matching number, doubled for every other one. `None` if more
+    /// than 64 numbers match, the points don't fit in a u64 then.
+    pub fn points(&self) -> Option<u64> {
+        match self.match_count() {
+            0 => Some(0),
+            n => u32::try_from(n - 1).ok().and_then(|n| 1_u64.checked_shl(n)),
         }
+    }
 
-        total_cards
+    /// Total of the original cards and every copy won, prizes past the last card are dropped
+    /// as with [`PrizeOverflow::Clamp`].
+    ///
+    /// Fails with [`ScratchcardError::DuplicateId`] if two cards have the same id, and with
+    /// [`ScratchcardError::MissingCard`] if a card wins a copy of an id in a gap between
+    /// the others.
+    pub fn total_scratchcards(cards: &[Card]) -> Result<u64, ScratchcardError> {
+        ScratchcardGame::new(cards)?.total(PrizeOverflow::Clamp)
     }
 
     pub fn id(&self) -> u32 {
         self.id
     }
 }
+
+/// What happens to prizes past the card with the highest id.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrizeOverflow {
+    /// They're dropped.
+    #[default]
+    Clamp,
+    /// They're an error.
+    Error,
+    /// They continue from the card with the lowest id. Those cards have already been
+    /// scratched, so the copies count but win nothing themselves.
+    Wrap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScratchcardError {
+    DuplicateId(u32),
+    /// `card` wins a copy of `prize`, which is past the last card.
+    PastLastCard {
+        card: u32,
+        prize: u32,
+    },
+    /// `card` wins a copy of `prize`, but there's no card with that id.
+    MissingCard {
+        card: u32,
+        prize: u32,
+    },
+    /// The copies of `card` don't fit in a u64.
+    TooManyCopies {
+        card: u32,
+    },
+    /// The copies of all cards together don't fit in a u64.
+    TooManyCards,
+    /// `card` has more than 64 matching numbers, see [`Card::points`].
+    TooManyPoints {
+        card: u32,
+    },
+}
+
+impl Display for ScratchcardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScratchcardError::DuplicateId(id) => write!(f, "there are several cards {id}"),
+            ScratchcardError::PastLastCard { card, prize } => {
+                write!(f, "card {card} wins card {prize}, past the last card")
+            }
+            ScratchcardError::MissingCard { card, prize } => {
+                write!(f, "card {card} wins card {prize}, which doesn't exist")
+            }
+            ScratchcardError::TooManyCopies { card } => {
+                write!(f, "card {card} has more copies than fit in a u64")
+            }
+            ScratchcardError::TooManyCards => {
+                write!(f, "there are more scratchcards than fit in a u64")
+            }
+            ScratchcardError::TooManyPoints { card } => {
+                write!(f, "card {card} is worth more points than fit in a u64")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScratchcardError {}
+
+/// Cards that win copies of the cards with the next ids, see [`ScratchcardGame::copies`].
+#[derive(Debug, Clone)]
+pub struct ScratchcardGame<'a> {
+    cards: &'a [Card],
+    /// Position of every card by id.
+    positions: HashMap<u32, usize>,
+    /// Positions ordered by id.
+    order: Vec<usize>,
+}
+
+impl<'a> ScratchcardGame<'a> {
+    /// Cards can be in any order, but every id has to be unique.
+    pub fn new(cards: &'a [Card]) -> Result<Self, ScratchcardError> {
+        let mut positions = HashMap::new();
+        for (i, card) in cards.iter().enumerate() {
+            if positions.insert(card.id, i).is_some() {
+                return Err(ScratchcardError::DuplicateId(card.id));
+            }
+        }
+        let mut order: Vec<_> = (0..cards.len()).collect();
+        order.sort_by_key(|&i| cards[i].id);
+
+        Ok(Self {
+            cards,
+            positions,
+            order,
+        })
+    }
+
+    pub fn cards(&self) -> &'a [Card] {
+        self.cards
+    }
+
+    pub fn card(&self, id: u32) -> Option<&'a Card> {
+        self.positions.get(&id).map(|&i| &self.cards[i])
+    }
+
+    /// Number of copies of every card, the original included, by id.
+    ///
+    /// A card with `n` matching numbers wins one copy of each of the cards with the next `n`
+    /// ids, for every copy of it. Cards are scratched in order of id.
+    pub fn copies(&self, overflow: PrizeOverflow) -> Result<BTreeMap<u32, u64>, ScratchcardError> {
+        let counts = self.play(overflow, |_, _, _| Ok(()))?;
+
+        Ok(self
+            .cards
+            .iter()
+            .zip(counts)
+            .map(|(card, count)| (card.id, count))
+            .collect())
+    }
+
+    pub fn total(&self, overflow: PrizeOverflow) -> Result<u64, ScratchcardError> {
+        total_of(self.play(overflow, |_, _, _| Ok(()))?)
+    }
+
+    /// Like [`ScratchcardGame::copies`], but also records which cards the copies came from.
+    pub fn provenance(&self, overflow: PrizeOverflow) -> Result<Provenance, ScratchcardError> {
+        let mut sources = vec![BTreeMap::new(); self.cards.len()];
+        let counts = self.play(overflow, |from, won, count| {
+            let copies = sources[won].entry(self.cards[from].id).or_insert(0_u64);
+            *copies = copies
+                .checked_add(count)
+                .ok_or(ScratchcardError::TooManyCopies {
+                    card: self.cards[won].id,
+                })?;
+            Ok(())
+        })?;
+
+        let total = total_of(counts.iter().copied())?;
+        let cards = self
+            .cards
+            .iter()
+            .zip(counts.into_iter().zip(sources))
+            .map(|(card, (copies, sources))| (card.id, CardProvenance { copies, sources }))
+            .collect();
+        Ok(Provenance { cards, total })
+    }
+
+    /// Scratches every card and returns the copies by position, calling `on_prize` with the
+    /// positions of the winning and won cards and the number of copies won.
+    fn play(
+        &self,
+        overflow: PrizeOverflow,
+        mut on_prize: impl FnMut(usize, usize, u64) -> Result<(), ScratchcardError>,
+    ) -> Result<Vec<u64>, ScratchcardError> {
+        let mut counts = vec![1_u64; self.cards.len()];
+        let (Some(&first), Some(&last)) = (self.order.first(), self.order.last()) else {
+            return Ok(counts);
+        };
+        let (first, last) = (self.cards[first].id, self.cards[last].id);
+
+        for &i in &self.order {
+            let card = &self.cards[i];
+            let count = counts[i];
+            for k in 1..=card.match_count() as u64 {
+                let prize = u64::from(card.id) + k;
+                let prize = if prize <= u64::from(last) {
+                    prize
+                } else {
+                    match overflow {
+                        PrizeOverflow::Clamp => break,
+                        PrizeOverflow::Error => {
+                            return Err(ScratchcardError::PastLastCard {
+                                card: card.id,
+                                prize: u32::try_from(prize).unwrap_or(u32::MAX),
+                            })
+                        }
+                        PrizeOverflow::Wrap => {
+                            let span = u64::from(last - first) + 1;
+                            u64::from(first) + (prize - u64::from(last) - 1) % span
+                        }
+                    }
+                };
+                // at most `last` here
+                let prize = prize as u32;
+                let Some(&won) = self.positions.get(&prize) else {
+                    return Err(ScratchcardError::MissingCard {
+                        card: card.id,
+                        prize,
+                    });
+                };
+                counts[won] = counts[won]
+                    .checked_add(count)
+                    .ok_or(ScratchcardError::TooManyCopies { card: prize })?;
+                on_prize(i, won, count)?;
+            }
+        }
+
+        Ok(counts)
+    }
+}
+
+fn total_of(counts: impl IntoIterator<Item = u64>) -> Result<u64, ScratchcardError> {
+    counts
+        .into_iter()
+        .try_fold(0_u64, u64::checked_add)
+        .ok_or(ScratchcardError::TooManyCards)
+}
+
+/// How many copies of a card there are, and which cards won them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardProvenance {
+    copies: u64,
+    sources: BTreeMap<u32, u64>,
+}
+
+impl CardProvenance {
+    /// Every copy, the original included.
+    pub fn copies(&self) -> u64 {
+        self.copies
+    }
+
+    /// Copies won by each card, by id.
+    pub fn sources(&self) -> &BTreeMap<u32, u64> {
+        &self.sources
+    }
+}
+
+/// Where the copies of every card came from, see [`ScratchcardGame::provenance`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    cards: BTreeMap<u32, CardProvenance>,
+    total: u64,
+}
+
+impl Provenance {
+    pub fn card(&self, id: u32) -> Option<&CardProvenance> {
+        self.cards.get(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &CardProvenance)> {
+        self.cards.iter().map(|(&id, card)| (id, card))
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// The `n` cards that won the most copies of card `id`, most first.
+    pub fn top_contributors(&self, id: u32, n: usize) -> Vec<(u32, u64)> {
+        let Some(card) = self.cards.get(&id) else {
+            return Vec::new();
+        };
+        let mut sources: Vec<_> = card.sources.iter().map(|(&id, &n)| (id, n)).collect();
+        sources.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        sources.truncate(n);
+        sources
+    }
+
+    /// The copies of card `id` broken down by the cards that won them, and theirs in turn, at
+    /// most `depth` levels deep.
+    pub fn tree(&self, id: u32, depth: usize) -> String {
+        let mut tree = String::new();
+        if let Some(card) = self.cards.get(&id) {
+            tree.push_str(&format!("card {id}: {} copies\n", card.copies));
+            self.write_sources(&mut tree, id, depth, 1);
+        }
+        tree
+    }
+
+    fn write_sources(&self, tree: &mut String, id: u32, depth: usize, level: usize) {
+        let indent = "  ".repeat(level);
+        if level > depth {
+            tree.push_str(&format!("{indent}...\n"));
+            return;
+        }
+        tree.push_str(&format!("{indent}original: 1\n"));
+        for (&source, &copies) in &self.cards[&id].sources {
+            tree.push_str(&format!("{indent}from card {source}: {copies}\n"));
+            self.write_sources(tree, source, depth, level + 1);
+        }
+    }
+
+    /// Graphviz graph with an edge from every card to each card it won copies of, labelled
+    /// with the number of copies.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph scratchcards {\n");
+        for (id, card) in &self.cards {
+            dot.push_str(&format!(
+                "    {id} [label=\"card {id}\\n{} copies\"];\n",
+                card.copies
+            ));
+        }
+        for (id, card) in &self.cards {
+            for (source, copies) in &card.sources {
+                dot.push_str(&format!("    {source} -> {id} [label=\"{copies}\"];\n"));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        let line = InputLine::new(1, s);
+        let card = line.strip_prefix(s.trim(), "Card ")?;
+        let (id, numbers) = line.split_once(card.trim(), ": ")?;
+        let id = line.parse(id, "a card id")?;
+        // numbers = 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        let (winning, hand) = line.split_once(numbers, " | ")?;
+        let winning = parse_numbers(line, winning, "a winning number")?;
+        let hand = parse_numbers(line, hand, "a number")?;
+
+        Ok(Card { id, winning, hand })
+    }
+}
+
+/// Whitespace separated numbers, plain digits are read without going through `str::parse`.
+fn parse_numbers(line: InputLine<'_>, s: &str, expected: &str) -> Result<NumberSet, ParseError> {
+    let mut numbers = NumberSet::new();
+    for token in s.split_whitespace() {
+        let digits = token.bytes().try_fold(0_u32, |n, b| {
+            let digit = (b as char).to_digit(10)?;
+            n.checked_mul(10)?.checked_add(digit)
+        });
+        let n = match digits {
+            Some(n) => n,
+            None => line.parse(token, expected)?,
+        };
+        numbers.insert(n);
+    }
+
+    Ok(numbers)
+}
+
+/// Writes the card with its numbers sorted, the original order is not kept.
+impl Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn sorted(numbers: &NumberSet) -> String {
+            let numbers: Vec<_> = numbers.iter().map(|n| format!("{n:>2}")).collect();
+            numbers.join(" ")
+        }
+
+        write!(
+            f,
+            "Card {}: {} | {}",
+            self.id,
+            sorted(&self.winning),
+            sorted(&self.hand)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_test() {
+        let card: Card = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"
+            .parse()
+            .unwrap();
+        assert_eq!(card.id(), 1);
+        assert_eq!(card.winning_numbers().count(), 4);
+        assert_eq!(card.to_string().parse::<Card>(), Ok(card));
+    }
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.lines().map(|l| l.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn points_test() {
+        let card: Card = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"
+            .parse()
+            .unwrap();
+        assert_eq!(card.points(), Some(8));
+        let card: Card = "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
+            .parse()
+            .unwrap();
+        assert_eq!(card.points(), Some(0));
+
+        let card = Card::new(1, 1..=64, 1..=64);
+        assert_eq!(card.points(), Some(1 << 63));
+        let card = Card::new(1, 1..=65, 1..=65);
+        assert_eq!(card.points(), None);
+    }
+
+    #[test]
+    fn copies_test() {
+        // out of order, with ids that don't start at 1
+        let cards = cards(
+            "Card 12: 1 2 | 1 2\n\
+             Card 10: 1 2 | 1 2\n\
+             Card 11: 1 2 | 1 3",
+        );
+        let game = ScratchcardGame::new(&cards).unwrap();
+        assert_eq!(game.card(11).map(Card::match_count), Some(1));
+        let copies: Vec<_> = game
+            .copies(PrizeOverflow::Clamp)
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(copies, [(10, 1), (11, 2), (12, 4)]);
+
+        assert_eq!(
+            game.copies(PrizeOverflow::Error),
+            Err(ScratchcardError::PastLastCard {
+                card: 12,
+                prize: 13
+            })
+        );
+        // card 12 wins 10 and 11 again, 4 copies each
+        assert_eq!(game.total(PrizeOverflow::Wrap), Ok(5 + 6 + 4));
+        assert_eq!(Card::total_scratchcards(&cards), Ok(7));
+    }
+
+    #[test]
+    fn copies_error_test() {
+        let duplicate = cards("Card 1: 1 | 2\nCard 1: 1 | 1");
+        assert_eq!(
+            ScratchcardGame::new(&duplicate).unwrap_err(),
+            ScratchcardError::DuplicateId(1)
+        );
+
+        assert_eq!(
+            Card::total_scratchcards(&duplicate),
+            Err(ScratchcardError::DuplicateId(1))
+        );
+
+        // every card wins all the later ones, so card n has 2^(n - 1) copies
+        let doubling = |n: u32| -> Vec<_> {
+            (1..=n)
+                .map(|id| Card::new(id, 1..=n - id, 1..=n - id))
+                .collect()
+        };
+        let deck = doubling(64);
+        let game = ScratchcardGame::new(&deck).unwrap();
+        assert_eq!(game.copies(PrizeOverflow::Clamp).unwrap()[&64], 1 << 63);
+        assert_eq!(game.total(PrizeOverflow::Clamp), Ok(u64::MAX));
+
+        let deck = doubling(65);
+        let game = ScratchcardGame::new(&deck).unwrap();
+        let too_many = Err(ScratchcardError::TooManyCopies { card: 65 });
+        assert_eq!(game.total(PrizeOverflow::Clamp), too_many);
+        assert_eq!(
+            game.provenance(PrizeOverflow::Clamp).map(|_| ()),
+            too_many.map(|_| ())
+        );
+
+        let mut deck = doubling(64);
+        deck.push(Card::new(65, [1], [2]));
+        let game = ScratchcardGame::new(&deck).unwrap();
+        let too_many = Err(ScratchcardError::TooManyCards);
+        assert_eq!(game.total(PrizeOverflow::Clamp), too_many);
+        assert_eq!(
+            game.provenance(PrizeOverflow::Clamp).map(|_| ()),
+            too_many.map(|_| ())
+        );
+
+        let gap = cards("Card 1: 1 | 1\nCard 3: 1 | 2");
+        let game = ScratchcardGame::new(&gap).unwrap();
+        assert_eq!(
+            game.total(PrizeOverflow::Clamp).unwrap_err().to_string(),
+            "card 1 wins card 2, which doesn't exist"
+        );
+        // the gap is an error whatever the policy for prizes past the end
+        for overflow in [PrizeOverflow::Error, PrizeOverflow::Wrap] {
+            assert_eq!(
+                game.total(overflow),
+                Err(ScratchcardError::MissingCard { card: 1, prize: 2 })
+            );
+        }
+        assert_eq!(
+            Card::total_scratchcards(&gap),
+            Err(ScratchcardError::MissingCard { card: 1, prize: 2 })
+        );
+        // a gap no card wins a copy of is fine
+        let unreached = cards("Card 1: 1 | 2\nCard 3: 1 | 1");
+        assert_eq!(Card::total_scratchcards(&unreached), Ok(2));
+    }
+
+    #[test]
+    fn number_set_test() {
+        let set: NumberSet = [5, 300, 64, 5, 127, 1000].into_iter().collect();
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(300) && set.contains(127) && !set.contains(128));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [5, 64, 127, 300, 1000]);
+
+        let other: NumberSet = [1000, 64, 6, 299].into_iter().collect();
+        assert_eq!(set.intersection(&other).collect::<Vec<_>>(), [64, 1000]);
+        assert_eq!(set.intersection_len(&other), 2);
+        assert!(NumberSet::new().is_empty());
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let error = "Card 1: 4x 2 | 1".parse::<Card>().unwrap_err();
+        assert_eq!((error.text(), error.expected()), ("4x", "a winning number"));
+        assert_eq!(error.column(), Some(9));
+        let card: Card = "Card 2: +3 | 3 4000000000"
+            .parse()
+            .unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(card.match_count(), 1);
+    }
+
+    #[test]
+    fn provenance_test() {
+        let cards = cards(
+            "Card 1: 1 2 | 1 2\n\
+             Card 2: 1 2 | 1 3\n\
+             Card 3: 1 2 | 3 4",
+        );
+        let game = ScratchcardGame::new(&cards).unwrap();
+        let provenance = game.provenance(PrizeOverflow::Clamp).unwrap();
+        assert_eq!(
+            provenance.total(),
+            game.total(PrizeOverflow::Clamp).unwrap()
+        );
+        assert_eq!(provenance.card(3).map(CardProvenance::copies), Some(4));
+        assert_eq!(provenance.top_contributors(3, 5), [(2, 2), (1, 1)]);
+        assert_eq!(provenance.top_contributors(3, 1), [(2, 2)]);
+        assert_eq!(
+            provenance.tree(3, 1),
+            "card 3: 4 copies
+  original: 1
+  from card 1: 1
+    ...
+  from card 2: 2
+    ...
+"
+        );
+        assert!(provenance.tree(3, 5).ends_with(
+            "  from card 2: 2
+    original: 1
+    from card 1: 1
+      original: 1
+"
+        ));
+        let dot = provenance.to_dot();
+        assert!(dot.starts_with("digraph scratchcards {\n    1 [label=\"card 1\\n1 copies\"];"));
+        assert!(dot.contains("    2 -> 3 [label=\"2\"];\n"));
+    }
+}
@@ -1,4 +1,8 @@
-use std::{cmp::Ordering, collections::VecDeque, os::raw};
+use crate::{
+    error::ParseError,
+    parse::{input_lines, InputLine},
+};
+use std::{fmt::Display, str::FromStr};
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub struct Range {
@@ -11,55 +15,6 @@ impl Range {
         Self { start, end }
     }
 
-    fn overlap(&self, source: Self) -> Option<(Self, Vec<Self>)> {
-        if source.start >= self.start && source.end <= self.end {
-            // self:    ----------|##########|----------
-            // source:  -------------|######|-----------
-            // overlap: -------------|######|-----------
-            // remain:  --------------------------------
-            return Some((Self::new(source.start, source.end), Vec::new()));
-        }
-        if source.start >= self.start && source.start < self.end && source.end >= self.end {
-            // self:    ----------|##########|----------
-            // source:  -------------|#########|--------
-            // overlap: -------------|#######|----------
-            // remain:  ---------------------|#|--------
-            return Some((
-                Self::new(source.start, self.end),
-                vec![Self::new(self.end, source.end)],
-            ));
-        }
-        if source.start < self.start && self.start < source.end && source.end <= self.end {
-            // self:    ----------|##########|----------
-            // source:  --------|###########|-----------
-            // overlap: ----------|#########|-----------
-            // remain:  --------|#|---------------------
-            return Some((
-                Self::new(self.start, source.end),
-                vec![Self::new(source.start, self.start)],
-            ));
-        }
-        if source.start < self.start && source.end > self.end {
-            // self:    ----------|##########|----------
-            // source:  --------|##############|--------
-            // overlap: ----------|##########|----------
-            // remain:  --------|#|----------|#|--------
-            return Some((
-                Self::new(self.start, self.end),
-                vec![
-                    Self::new(source.start, self.start),
-                    Self::new(self.end, source.end),
-                ],
-            ));
-        }
-
-        // self:    ----------|##########|----------
-        // source:  ---|###|------------------------
-        // overlap: --------------------------------
-        // remain:  ---|###|------------------------
-        None
-    }
-
     pub fn start(&self) -> u64 {
         self.start
     }
@@ -67,48 +22,72 @@ impl Range {
     pub fn end(&self) -> u64 {
         self.end
     }
+
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    fn overlaps(&self, other: &Range) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// Empty if they don't overlap.
+    fn intersection(&self, other: &Range) -> Range {
+        let start = self.start.max(other.start);
+        Range::new(start, self.end.min(other.end).max(start))
+    }
+}
+
+/// Sorts `ranges` and joins those that overlap or touch, dropping empty ones.
+fn merge(mut ranges: Vec<Range>) -> Vec<Range> {
+    ranges.retain(|r| !r.is_empty());
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+
+    merged
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Map {
     dest: Range,
     source: Range,
 }
 
 impl Map {
+    /// Panics if either range ends past `u64::MAX`.
     pub fn new(dest: u64, source: u64, len: u64) -> Self {
+        let end = |start: u64| start.checked_add(len).expect("range ends within u64");
         Self {
-            dest: Range::new(dest, dest + len),
-            source: Range::new(source, source + len),
+            dest: Range::new(dest, end(dest)),
+            source: Range::new(source, end(source)),
         }
     }
 
-    fn convert(&self, input: Range) -> Option<(Range, Vec<Range>)> {
-        if let Some((overlap, remaining)) = self.source.overlap(input) {
-            match self.dest.start.cmp(&self.source.start) {
-                Ordering::Less => {
-                    let diff = self.source.start - self.dest.start;
-                    // subtract diff to get to dest
-                    Some((
-                        Range::new(overlap.start - diff, overlap.end - diff),
-                        remaining,
-                    ))
-                }
-                Ordering::Greater => {
-                    let diff = self.dest.start - self.source.start;
-                    Some((
-                        Range::new(overlap.start + diff, overlap.end + diff),
-                        remaining,
-                    ))
-                }
-                _ => unreachable!(),
-            }
-        } else {
-            None
-        }
+    pub fn dest(&self) -> Range {
+        self.dest
+    }
+
+    pub fn source(&self) -> Range {
+        self.source
+    }
+
+    /// Where `value`, at most the end of the source range, goes.
+    fn map(&self, value: u64) -> u64 {
+        self.dest.start + (value - self.source.start)
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
 pub struct Almanac {
     mappings: Vec<Mappings>,
 }
@@ -118,83 +97,349 @@ impl Almanac {
         Self { mappings }
     }
 
+    pub fn mappings(&self) -> &[Mappings] {
+        &self.mappings
+    }
+
+    /// Sends `input` through every mapping in turn.
     pub fn convert(&self, input: Range) -> Vec<Range> {
-        let mut stack = vec![input];
-        for i in 0..self.mappings.len() {
-            let mapping = &self.mappings[i];
-            let mut next_stack = Vec::new();
-            while let Some(range) = stack.pop() {
-                let mut output_ranges = mapping.convert(range);
-                next_stack.append(&mut output_ranges);
-            }
-            stack = next_stack;
-            if i == self.mappings.len() - 1 {
-                // last mapping
-                return stack;
-            }
+        self.mappings.iter().fold(vec![input], |ranges, mapping| {
+            ranges
+                .into_iter()
+                .flat_map(|range| mapping.convert(range))
+                .collect()
+        })
+    }
+
+    /// All the mappings composed into one, from the first source to the last destination.
+    /// Converting through it gives the same values as [`Almanac::convert`] in a single step.
+    pub fn flatten(&self) -> Mappings {
+        match self.mappings.split_first() {
+            Some((first, rest)) => rest
+                .iter()
+                .fold(first.clone(), |all, next| all.compose(next)),
+            None => Mappings::identity(String::new()),
         }
+    }
+
+    /// The sorted and merged ranges of first sources that end up in `target`.
+    /// Flattens on every call, [`Mappings::inverse`] on [`Almanac::flatten`] is cheaper
+    /// for many queries.
+    pub fn inverse(&self, target: Range) -> Vec<Range> {
+        self.flatten().inverse(target)
+    }
+
+    /// The lowest value any of `inputs` ends up at, and the parts of `inputs` that get there.
+    pub fn lowest(&self, inputs: &[Range]) -> Option<(u64, Vec<Range>)> {
+        let flat = self.flatten();
+        let lowest = inputs
+            .iter()
+            .flat_map(|&input| flat.convert(input))
+            .map(|r| r.start)
+            .min()?;
+        let sources = flat.inverse(Range::new(lowest, lowest + 1));
+        let reaching = inputs
+            .iter()
+            .flat_map(|input| sources.iter().map(|s| s.intersection(input)))
+            .collect();
 
-        unreachable!()
+        Some((lowest, merge(reaching)))
     }
 }
 
-#[derive(Debug)]
+/// Maps sorted by source, which can't overlap. Values that no map covers stay the same.
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Mappings {
     name: String,
+    /// In the order they were given, to write them back the same way.
     maps: Vec<Map>,
+    /// Non-empty maps, by source start.
+    by_source: Vec<Map>,
 }
 
 impl Mappings {
-    pub fn new(name: String, maps: Vec<Map>) -> Self {
-        Self { name, maps }
+    /// Fails if the sources of two maps overlap.
+    pub fn new(name: String, maps: Vec<Map>) -> Result<Self, ParseError> {
+        if let Some((first, second)) = Self::overlapping(&maps) {
+            return Err(ParseError::new(
+                maps[second].to_string(),
+                format!("a map that doesn't overlap '{}'", maps[first]),
+            ));
+        }
+        let mut by_source: Vec<_> = maps
+            .iter()
+            .copied()
+            .filter(|m| !m.source.is_empty())
+            .collect();
+        by_source.sort_by_key(|m| m.source.start);
+
+        Ok(Self {
+            name,
+            maps,
+            by_source,
+        })
+    }
+
+    /// Positions of two maps whose sources overlap, in the order they were given.
+    fn overlapping(maps: &[Map]) -> Option<(usize, usize)> {
+        let mut order: Vec<_> = (0..maps.len())
+            .filter(|&i| !maps[i].source.is_empty())
+            .collect();
+        order.sort_by_key(|&i| maps[i].source.start);
+        // with sorted starts, a map can only overlap the one right before it
+        order
+            .windows(2)
+            .find(|w| maps[w[0]].source.overlaps(&maps[w[1]].source))
+            .map(|w| (w[0].min(w[1]), w[0].max(w[1])))
     }
 
+    /// Leaves every value the same.
+    pub fn identity(name: String) -> Self {
+        Self {
+            name,
+            maps: Vec::new(),
+            by_source: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// In the order they were given.
+    pub fn maps(&self) -> &[Map] {
+        &self.maps
+    }
+
+    /// Splits `input` into the pieces the maps cover and those they don't, and maps the
+    /// covered ones. Pieces are in the order of the part of `input` they come from, and
+    /// their lengths add up to that of `input`.
     pub fn convert(&self, input: Range) -> Vec<Range> {
-        let mut output_ranges = Vec::new();
-        let mut queue = VecDeque::new();
-        queue.push_back(input);
-        let mut count = 0;
-        while let Some(input_range) = queue.pop_front() {
-            count += 1;
-            let mut some_overlap = false;
-            for map in &self.maps {
-                if let Some((converted, remaining)) = map.convert(input_range) {
-                    output_ranges.push(converted);
-                    some_overlap = true;
-                    for r in remaining {
-                        queue.push_back(r);
+        self.split(input)
+            .into_iter()
+            .map(|(piece, map)| match map {
+                Some(map) => Range::new(map.map(piece.start), map.map(piece.end)),
+                None => piece,
+            })
+            .collect()
+    }
+
+    /// Splits `input` in order into pieces, each with the map covering it if there is one.
+    fn split(&self, input: Range) -> Vec<(Range, Option<&Map>)> {
+        let mut pieces = Vec::new();
+        let mut at = input.start;
+        // the first map that ends after `at`, later maps start after it
+        let mut next = self.by_source.partition_point(|m| m.source.end <= at);
+        while at < input.end {
+            match self.by_source.get(next) {
+                Some(map) if map.source.start < input.end => {
+                    if at < map.source.start {
+                        pieces.push((Range::new(at, map.source.start), None));
+                        at = map.source.start;
                     }
-                    break;
+                    let end = map.source.end.min(input.end);
+                    pieces.push((Range::new(at, end), Some(map)));
+                    at = end;
+                    next += 1;
+                }
+                _ => {
+                    pieces.push((Range::new(at, input.end), None));
+                    at = input.end;
+                }
+            }
+        }
+
+        pieces
+    }
+
+    /// Applies `self`, then `next`. Pieces that end up unchanged are left out, and
+    /// neighbouring pieces that move by the same amount are joined.
+    pub fn compose(&self, next: &Mappings) -> Mappings {
+        let mut maps = Vec::new();
+        // values that `self` moves, then wherever `next` sends them
+        for map in &self.by_source {
+            for (piece, then) in next.split(map.dest) {
+                let source = map.source.start + (piece.start - map.dest.start);
+                let dest = then.map_or(piece.start, |then| then.map(piece.start));
+                maps.push(Map::new(dest, source, piece.len()));
+            }
+        }
+        // values that `self` leaves alone and `next` moves
+        for then in &next.by_source {
+            for (piece, map) in self.split(then.source) {
+                if map.is_none() {
+                    maps.push(Map::new(then.map(piece.start), piece.start, piece.len()));
                 }
             }
+        }
+        maps.retain(|m| m.dest.start != m.source.start);
+        maps.sort_by_key(|m| m.source.start);
+        let mut joined: Vec<Map> = Vec::with_capacity(maps.len());
+        for map in maps {
+            match joined.last_mut() {
+                Some(last)
+                    if last.source.end == map.source.start && last.dest.end == map.dest.start =>
+                {
+                    last.source.end = map.source.end;
+                    last.dest.end = map.dest.end;
+                }
+                _ => joined.push(map),
+            }
+        }
+
+        let name = match (self.name.split_once("-to-"), next.name.split_once("-to-")) {
+            (Some((from, _)), Some((_, to))) => format!("{from}-to-{to}"),
+            _ => format!("{} {}", self.name, next.name).trim().to_string(),
+        };
+        Mappings::new(name, joined).expect("pieces of non-overlapping sources don't overlap")
+    }
+
+    /// The sorted and merged ranges of sources that end up in `target`.
+    pub fn inverse(&self, target: Range) -> Vec<Range> {
+        let mut sources: Vec<_> = self
+            .by_source
+            .iter()
+            .filter_map(|map| {
+                let piece = map.dest.intersection(&target);
+                (!piece.is_empty()).then(|| {
+                    let start = map.source.start + (piece.start - map.dest.start);
+                    Range::new(start, start + piece.len())
+                })
+            })
+            .collect();
+        // values no map covers stay where they are
+        sources.extend(
+            self.split(target)
+                .into_iter()
+                .filter(|(_, map)| map.is_none())
+                .map(|(piece, _)| piece),
+        );
+
+        merge(sources)
+    }
+}
+
+impl Display for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.dest.start,
+            self.source.start,
+            self.source.end - self.source.start
+        )
+    }
+}
 
-            if !some_overlap {
-                output_ranges.push(input_range);
+impl Mappings {
+    /// Parses a header line followed by one map per line, keeping line numbers for errors.
+    pub(crate) fn from_lines(lines: &[InputLine]) -> Result<Self, ParseError> {
+        let (header, lines) = lines
+            .split_first()
+            .ok_or_else(|| ParseError::missing_line(1, "a map header"))?;
+        if !header.text().ends_with(" map:") {
+            return Err(header.error(header.text(), "a '<from>-to-<to> map:' header"));
+        }
+        let mut maps = Vec::with_capacity(lines.len());
+        for line in lines {
+            let parts: Vec<u64> = line
+                .text()
+                .split(' ')
+                .map(|p| line.parse(p, "a number"))
+                .collect::<Result<_, _>>()?;
+            if parts.len() != 3 {
+                return Err(line.error(line.text(), "three numbers"));
+            }
+            if parts[0].checked_add(parts[2]).is_none() || parts[1].checked_add(parts[2]).is_none()
+            {
+                return Err(line.error(line.text(), "a map that ends within 64 bits"));
             }
+            maps.push(Map::new(parts[0], parts[1], parts[2]));
+        }
+        if let Some((first, second)) = Self::overlapping(&maps) {
+            let first = format!("a map that doesn't overlap line {}", lines[first].number());
+            return Err(lines[second].error(lines[second].text(), &first));
+        }
+
+        Mappings::new(header.text().to_string(), maps)
+    }
+}
+
+impl FromStr for Mappings {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<_> = input_lines(s).collect();
+        Self::from_lines(&lines)
+    }
+}
+
+impl Display for Mappings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        for map in &self.maps {
+            write!(f, "\n{map}")?;
+        }
 
-            if count > 10 {
-                break;
+        Ok(())
+    }
+}
+
+impl Almanac {
+    /// Parses blocks of mappings separated by empty lines, keeping line numbers for errors.
+    pub(crate) fn from_lines(lines: &[InputLine]) -> Result<Self, ParseError> {
+        let mappings = lines
+            .split(|l| l.text().trim().is_empty())
+            .filter(|block| !block.is_empty())
+            .map(Mappings::from_lines)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(mappings))
+    }
+}
+
+impl FromStr for Almanac {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<_> = input_lines(s).collect();
+        Self::from_lines(&lines)
+    }
+}
+
+impl Display for Almanac {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, mappings) in self.mappings.iter().enumerate() {
+            if i > 0 {
+                write!(f, "\n\n")?;
             }
+            write!(f, "{mappings}")?;
         }
 
-        output_ranges
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bench::Lcg;
 
     #[test]
     fn convert_test() {
-        let map = Map::new(52, 50, 48);
-
+        let single = |map| Mappings::new("".to_string(), vec![map]).unwrap();
         assert_eq!(
-            map.convert(Range::new(79, 79 + 14)),
-            Some((Range::new(81, 95), Vec::new()))
+            single(Map::new(52, 50, 48)).convert(Range::new(79, 79 + 14)),
+            vec![Range::new(81, 95)]
+        );
+        assert_eq!(
+            single(Map::new(81, 45, 19)).convert(Range::new(74, 88)),
+            vec![Range::new(74, 88)]
+        );
+        assert_eq!(
+            single(Map::new(10, 10, 5)).convert(Range::new(8, 20)),
+            vec![Range::new(8, 10), Range::new(10, 15), Range::new(15, 20)]
         );
-
-        assert_eq!(Map::new(81, 45, 19).convert(Range::new(74, 88)), None);
 
         let mappings = Mappings::new(
             "".to_string(),
@@ -203,12 +448,161 @@ mod tests {
                 Map::new(81, 45, 19),
                 Map::new(68, 64, 13),
             ],
-        );
+        )
+        .unwrap();
 
         assert_eq!(
             mappings.convert(Range::new(74, 88)),
-            vec![Range::new(45, 56), Range::new(78, 81)]
+            vec![Range::new(78, 81), Range::new(45, 56)]
         );
+        // more pieces than there used to be room for
+        let maps = (0..20).map(|i| Map::new(1000 + i, 10 * i, 5)).collect();
+        let pieces = Mappings::new("".to_string(), maps)
+            .unwrap()
+            .convert(Range::new(0, 200));
+        assert_eq!(pieces.len(), 40);
+        assert_eq!(pieces.iter().map(Range::len).sum::<u64>(), 200);
+    }
+
+    #[test]
+    fn overlap_test() {
+        let error = Mappings::new(
+            "".to_string(),
+            vec![Map::new(0, 10, 5), Map::new(50, 0, 5), Map::new(20, 14, 3)],
+        )
+        .unwrap_err();
+        assert_eq!(
+            (error.text(), error.expected()),
+            ("20 14 3", "a map that doesn't overlap '0 10 5'")
+        );
+        // touching and empty maps are fine
+        assert!(Mappings::new(
+            "".to_string(),
+            vec![Map::new(0, 10, 5), Map::new(50, 5, 5), Map::new(9, 12, 0)],
+        )
+        .is_ok());
+
+        let error = "seed-to-soil map:\n50 98 2\n0 0 10\n20 5 4"
+            .parse::<Mappings>()
+            .unwrap_err();
+        assert_eq!(
+            (error.line(), error.expected()),
+            (Some(4), "a map that doesn't overlap line 3")
+        );
+        let error = format!("seed-to-soil map:\n0 {} 2", u64::MAX)
+            .parse::<Mappings>()
+            .unwrap_err();
+        assert_eq!(error.line(), Some(2));
+    }
+
+    /// Where `value` ends up, trying every map of every mapping.
+    fn brute_force(mappings: &[Vec<(u64, u64, u64)>], value: u64) -> u64 {
+        mappings.iter().fold(value, |value, maps| {
+            maps.iter()
+                .find(|&&(_, source, len)| (source..source + len).contains(&value))
+                .map_or(value, |&(dest, source, _)| dest + value - source)
+        })
+    }
+
+    #[test]
+    fn random_almanac_test() {
+        let mut lcg = Lcg::new(0x5eed);
+        let mut random = |n: u64| lcg.below(n);
+        for _ in 0..200 {
+            let mut mappings = Vec::new();
+            for _ in 0..=random(4) {
+                // cut 0..100 at random points and keep some of the pieces as sources
+                let mut cuts: Vec<_> = (0..random(12)).map(|_| random(100)).collect();
+                cuts.sort();
+                let mut maps = Vec::new();
+                for w in cuts.windows(2) {
+                    if random(3) > 0 {
+                        maps.push((random(150), w[0], w[1] - w[0]));
+                    }
+                }
+                // the order they're given in shouldn't matter
+                maps.reverse();
+                let turn = random(maps.len() as u64 + 1) as usize;
+                maps.rotate_left(turn);
+                mappings.push(maps);
+            }
+            let almanac = Almanac::new(
+                mappings
+                    .iter()
+                    .map(|maps| {
+                        let maps = maps.iter().map(|&(d, s, l)| Map::new(d, s, l)).collect();
+                        Mappings::new("".to_string(), maps).unwrap()
+                    })
+                    .collect(),
+            );
+
+            let start = random(160);
+            let input = Range::new(start, start + random(60));
+            let mut expected: Vec<_> = (input.start()..input.end())
+                .map(|v| brute_force(&mappings, v))
+                .collect();
+            let pieces = almanac.convert(input);
+            assert!(pieces.iter().all(|p| !p.is_empty()));
+            let mut actual: Vec<_> = pieces.iter().flat_map(|p| p.start()..p.end()).collect();
+            expected.sort();
+            actual.sort();
+            assert_eq!(actual, expected, "{input:?} through\n{almanac}");
+
+            let flat = almanac.flatten();
+            let mut actual: Vec<_> = flat
+                .convert(input)
+                .iter()
+                .flat_map(|p| p.start()..p.end())
+                .collect();
+            actual.sort();
+            assert_eq!(actual, expected, "{input:?} through\n{flat}");
+
+            // everything past 250 is left alone, and so stays out of the target
+            let start = random(200);
+            let target = Range::new(start, start + random(50));
+            let expected: Vec<_> = (0..250)
+                .filter(|&v| (target.start()..target.end()).contains(&brute_force(&mappings, v)))
+                .collect();
+            let sources = flat.inverse(target);
+            assert_eq!(sources, merge(sources.clone()));
+            let actual: Vec<_> = sources.iter().flat_map(|p| p.start()..p.end()).collect();
+            assert_eq!(actual, expected, "{target:?} back through\n{flat}");
+        }
+    }
+
+    #[test]
+    fn flatten_test() {
+        let input = "seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15";
+        let almanac: Almanac = input.parse().unwrap();
+        assert_eq!(
+            almanac.flatten().to_string(),
+            "seed-to-fertilizer map:
+39 0 15
+0 15 35
+37 50 2
+54 52 46
+35 98 2"
+        );
+        assert_eq!(almanac.inverse(Range::new(0, 2)), vec![Range::new(15, 17)]);
+        assert_eq!(Almanac::new(Vec::new()).flatten().maps(), &[]);
+    }
+
+    #[test]
+    fn lowest_test() {
+        let (_, almanac) =
+            crate::day5::parse_almanac(&crate::example_input(5, "example1.txt")).unwrap();
+        assert_eq!(
+            almanac.lowest(&[Range::new(79, 93), Range::new(55, 68)]),
+            Some((46, vec![Range::new(82, 83)]))
+        );
+        assert_eq!(almanac.lowest(&[]), None);
     }
 
     #[test]
@@ -217,7 +611,8 @@ mod tests {
             Mappings::new(
                 "seed-to-soil map:".to_string(),
                 vec![Map::new(50, 98, 2), Map::new(52, 50, 48)],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "soil-to-fertilizer map:".to_string(),
                 vec![
@@ -225,7 +620,8 @@ mod tests {
                     Map::new(37, 52, 2),
                     Map::new(39, 0, 15),
                 ],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "fertilizer-to-water map:".to_string(),
                 vec![
@@ -234,11 +630,13 @@ mod tests {
                     Map::new(42, 0, 7),
                     Map::new(57, 7, 4),
                 ],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "water-to-light map:".to_string(),
                 vec![Map::new(88, 18, 7), Map::new(18, 25, 70)],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "light-to-temperature map:".to_string(),
                 vec![
@@ -246,15 +644,18 @@ mod tests {
                     Map::new(81, 45, 19),
                     Map::new(68, 64, 13),
                 ],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "temperature-to-humidity map:".to_string(),
                 vec![Map::new(0, 69, 1), Map::new(1, 0, 69)],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "humidity-to-location map:".to_string(),
                 vec![Map::new(60, 56, 37), Map::new(56, 93, 4)],
-            ),
+            )
+            .unwrap(),
         ]);
 
         assert_eq!(
@@ -265,11 +666,12 @@ mod tests {
 
     #[test]
     fn convert_test_2() {
-        let mappings = vec![
+        let mappings = [
             Mappings::new(
                 "seed-to-soil map:".to_string(),
                 vec![Map::new(50, 98, 2), Map::new(52, 50, 48)],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "soil-to-fertilizer map:".to_string(),
                 vec![
@@ -277,7 +679,8 @@ mod tests {
                     Map::new(37, 52, 2),
                     Map::new(39, 0, 15),
                 ],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "fertilizer-to-water map:".to_string(),
                 vec![
@@ -286,11 +689,13 @@ mod tests {
                     Map::new(42, 0, 7),
                     Map::new(57, 7, 4),
                 ],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "water-to-light map:".to_string(),
                 vec![Map::new(88, 18, 7), Map::new(18, 25, 70)],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "light-to-temperature map:".to_string(),
                 vec![
@@ -298,15 +703,18 @@ mod tests {
                     Map::new(81, 45, 19),
                     Map::new(68, 64, 13),
                 ],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "temperature-to-humidity map:".to_string(),
                 vec![Map::new(0, 69, 1), Map::new(1, 0, 69)],
-            ),
+            )
+            .unwrap(),
             Mappings::new(
                 "humidity-to-location map:".to_string(),
                 vec![Map::new(60, 56, 37), Map::new(56, 93, 4)],
-            ),
+            )
+            .unwrap(),
         ];
 
         assert_eq!(
@@ -322,4 +730,24 @@ mod tests {
             vec![Range::new(41, 42)]
         );
     }
+
+    #[test]
+    fn round_trip_test() {
+        let input = "seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15";
+        let almanac: Almanac = input.parse().unwrap();
+        assert_eq!(almanac.to_string(), input);
+        assert_eq!(almanac.to_string().parse::<Almanac>(), Ok(almanac));
+
+        let error = "seed-to-soil map:\n50 98\n\nsoil"
+            .parse::<Almanac>()
+            .unwrap_err();
+        assert_eq!((error.line(), error.expected()), (Some(2), "three numbers"));
+    }
 }
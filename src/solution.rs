@@ -0,0 +1,140 @@
+use std::{fmt::Display, marker::PhantomData};
+
+use crate::{
+    day1, day10, day11, day2, day3, day4, day5, day6, day7, day8, day9, error::ParseError,
+};
+
+/// A single day of the puzzle, split into parsing and the two parts.
+pub trait Solution {
+    const DAY: u32;
+
+    type Input;
+    type Part1: Display;
+    type Part2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+
+    fn part1(input: &Self::Input) -> Self::Part1;
+
+    fn part2(input: &Self::Input) -> Self::Part2;
+}
+
+/// Answer to a part that can fail on input that parsed, shows the error when it does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fallible<T, E>(pub Result<T, E>);
+
+impl<T: Display, E: Display> Display for Fallible<T, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Ok(answer) => write!(f, "{answer}"),
+            Err(e) => write!(f, "error: {e}"),
+        }
+    }
+}
+
+/// Object safe version of [`Solution`], used to look days up at runtime.
+pub trait DynSolution: Sync {
+    fn day(&self) -> u32;
+
+    fn parse(&self, input: &str) -> Result<Box<dyn ParsedInput>, ParseError>;
+}
+
+/// Parsed input for some day, ready to be solved.
+pub trait ParsedInput {
+    fn part1(&self) -> String;
+
+    fn part2(&self) -> String;
+
+    fn solve(&self, part: u32) -> Option<String> {
+        match part {
+            1 => Some(self.part1()),
+            2 => Some(self.part2()),
+            _ => None,
+        }
+    }
+}
+
+struct Parsed<S: Solution> {
+    input: S::Input,
+    solution: PhantomData<S>,
+}
+
+impl<S: Solution> ParsedInput for Parsed<S> {
+    fn part1(&self) -> String {
+        S::part1(&self.input).to_string()
+    }
+
+    fn part2(&self) -> String {
+        S::part2(&self.input).to_string()
+    }
+}
+
+impl<S> DynSolution for S
+where
+    S: Solution + Sync + 'static,
+{
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn ParsedInput>, ParseError> {
+        Ok(Box::new(Parsed::<S> {
+            input: S::parse(input)?,
+            solution: PhantomData,
+        }))
+    }
+}
+
+static SOLUTIONS: [&dyn DynSolution; 11] = [
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+    &day7::Day7,
+    &day8::Day8,
+    &day9::Day9,
+    &day10::Day10,
+    &day11::Day11,
+];
+
+/// Returns the solution for `day`, if there is one.
+pub fn get(day: u32) -> Option<&'static dyn DynSolution> {
+    SOLUTIONS.iter().copied().find(|s| s.day() == day)
+}
+
+/// Returns all solutions, ordered by day.
+pub fn all() -> impl Iterator<Item = &'static dyn DynSolution> {
+    SOLUTIONS.iter().copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_test() {
+        assert_eq!(all().count(), 11);
+        for day in 1..=11 {
+            assert_eq!(get(day).map(|s| s.day()), Some(day));
+        }
+        assert!(get(0).is_none());
+        assert!(get(12).is_none());
+    }
+
+    #[test]
+    fn dyn_solution_test() {
+        let input = "0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45";
+        let parsed = get(9).unwrap().parse(input).unwrap();
+        assert_eq!(parsed.part1(), "114");
+        assert_eq!(parsed.solve(2).as_deref(), Some("2"));
+        assert_eq!(parsed.solve(3), None);
+
+        let error = get(9).unwrap().parse("1 2 x").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "day 9, line 1, column 5: expected a number, found 'x'"
+        );
+    }
+}